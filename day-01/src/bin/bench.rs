@@ -0,0 +1,74 @@
+// Compares the automaton scanner with searching each line once per word, on a
+// generated input of a few megabytes. Run with `cargo run --release --bin bench`.
+
+use std::env;
+use std::time::Instant;
+
+use day_01::{Extractor, Vocabulary};
+
+const WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+fn main() {
+    let megabytes: usize = env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("Usage: bench [megabytes]"))
+        .unwrap_or(8);
+    let input = generate(megabytes * 1024 * 1024);
+    println!("{} lines, {} bytes", input.lines().count(), input.len());
+
+    let vocabulary = Vocabulary::english();
+    let start = Instant::now();
+    let expected: u32 = input
+        .lines()
+        .map(|line| match_indices_value(&vocabulary, line))
+        .sum();
+    println!("match_indices: {:?}", start.elapsed());
+
+    let extractor = Extractor::new(vocabulary);
+    let start = Instant::now();
+    let result = extractor.sum(&input);
+    println!("automaton:     {:?}", start.elapsed());
+
+    assert_eq!(result, expected);
+    println!("Result = {}", result);
+}
+
+fn match_indices_value(vocabulary: &Vocabulary, line: &str) -> u32 {
+    let tokens = vocabulary.match_tokens(line);
+    let (Some(first), Some(last)) = (tokens.first(), tokens.last()) else {
+        return 0;
+    };
+    let last = tokens
+        .iter()
+        .find(|token| token.offset == last.offset)
+        .unwrap();
+    first.value * 10 + last.value
+}
+
+/// Lines of letters with a few words and digits mixed in, from a fixed seed so
+/// every run sees the same input.
+fn generate(len: usize) -> String {
+    let mut seed = 0x2023_1201u64;
+    let mut random = |below: usize| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize % below
+    };
+    let mut input = String::with_capacity(len + 100);
+    while input.len() < len {
+        let line_len = 20 + random(60);
+        let line_start = input.len();
+        while input.len() - line_start < line_len {
+            match random(20) {
+                0 => input.push(char::from(b'0' + random(10) as u8)),
+                1 | 2 => input.push_str(WORDS[random(WORDS.len())]),
+                _ => input.push(char::from(b'a' + random(26) as u8)),
+            }
+        }
+        input.push('\n');
+    }
+    input
+}
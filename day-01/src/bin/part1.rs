@@ -1,19 +1,27 @@
+use std::env;
+
+use day_01::explain::{explain, format_explanations};
+use day_01::{Extractor, Vocabulary};
+
 fn main() {
     println!("Part 1");
 
     let input = include_str!("./input1.txt");
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--explain" => {
+                let extractor = Extractor::new(Vocabulary::digits());
+                print!("{}", format_explanations(&explain(&extractor, input)));
+            }
+            _ => panic!("Usage: part1 [--explain]"),
+        }
+    }
     let output = parse_input(input);
     println!("{output}");
 }
 
 fn parse_input(input: &str) -> String {
-    let lines: Vec<&str> = input.split("\n").collect();
-    let mut result: u32 = 0;
-    for line in lines {
-        let digits: Vec<u32> = line.chars().filter_map(|c| c.to_digit(10)).collect();
-        result += digits.first().clone().unwrap_or(&0) * 10 + digits.last().unwrap_or(&0);
-    }
-    return result.to_string();
+    Extractor::new(Vocabulary::digits()).sum(input).to_string()
 }
 
 #[cfg(test)]
@@ -38,4 +46,3 @@ treb7uchet
         assert_eq!(output, "142".to_string());
     }
 }
-
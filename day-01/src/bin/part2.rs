@@ -1,98 +1,46 @@
-fn main() {
-    println!("Part 2");
-
-    let input = include_str!("./input2.txt");
-    let output = parse_input(input);
-    println!("{output}");
-}
-
-fn parse_input(input: &str) -> String {
-    let mut result: u32 = 0;
-    for line in input.lines() {
-        result += get_sum_first_last(line);
-    }
-
-    result.to_string()
-}
-
-fn get_sum_first_last(line: &str) -> u32 {
-    let word_numbers = get_first_last_number_of_words_index(line);
-    let digit_numbers = get_first_last_number_index(line);
-
-    let mut vec = Vec::new();
-
-    vec.extend(word_numbers.0);
-    vec.extend(word_numbers.1);
-    vec.extend(digit_numbers.0);
-    vec.extend(digit_numbers.1);
-
-    vec.sort_unstable_by_key(|x| x.0);
+use std::env;
+use std::fs;
 
-    // get first and last and sum
-    let mut sum = 0;
-    if let Some(first) = vec.first() {
-        sum += first.1 * 10;
-    };
-    if let Some(last) = vec.last() {
-        sum += last.1;
-    };
-    sum
-}
+use day_01::explain::{explain, format_explanations};
+use day_01::{Extractor, Vocabulary};
 
-fn convert_number(input: &str) -> u32 {
-    match input {
-        "one" => 1,
-        "two" => 2,
-        "three" => 3,
-        "four" => 4,
-        "five" => 5,
-        "six" => 6,
-        "seven" => 7,
-        "eight" => 8,
-        "nine" => 9,
-        _ => 0,
-    }
-}
+const USAGE: &str = "Usage: part2 [--config <file>] [--explain]";
 
-fn get_first_last_number_of_words_index(line: &str) -> (Option<(usize, u32)>, Option<(usize, u32)>) {
-    let numbers = [
-        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-    ];
-
-    let mut result = Vec::new();
-
-    for number in numbers {
-        result.append(&mut line.match_indices(number).collect());
-    }
-
-    result.sort_unstable_by_key(|x| x.0);
-
-    let mut tupel: (Option<(usize, u32)>, Option<(usize, u32)>) = (None, None);
-
-    if let Some(first) = result.first() {
-        tupel.0 = Some((first.0, convert_number(first.1)));
-    }
-    if let Some(last) = result.last() {
-        tupel.1 = Some((last.0, convert_number(last.1)));
-    }
-
-    tupel
-}
+fn main() {
+    println!("Part 2");
 
-fn get_first_last_number_index(line: &str) -> (Option<(usize, u32)>, Option<(usize, u32)>) {
-    let mut first_digit: Option<(usize, u32)> = None;
-    let mut last_digit: Option<(usize, u32)> = None;
-    
-    for (index, character) in line.char_indices() {
-        if let Some(digit) = character.to_digit(10) {
-            last_digit = Some((index, digit));
-            if first_digit.is_none() {
-                first_digit = Some((index, digit));
+    let input = include_str!("./input2.txt");
+    let mut extractor = None;
+    let mut show_explanation = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                let config = fs::read_to_string(args.next().expect(USAGE))
+                    .expect("Could not read the config");
+                extractor =
+                    Some(Extractor::from_config(&config).unwrap_or_else(|err| panic!("{}", err)));
             }
+            "--explain" => show_explanation = true,
+            _ => panic!("{}", USAGE),
         }
     }
+    if show_explanation {
+        let explanations = match &extractor {
+            None => explain(&Extractor::new(Vocabulary::english()), input),
+            Some(extractor) => explain(extractor, input),
+        };
+        print!("{}", format_explanations(&explanations));
+    }
+    let output = match extractor {
+        None => parse_input(input),
+        Some(extractor) => extractor.sum(input).to_string(),
+    };
+    println!("{output}");
+}
 
-    (first_digit, last_digit)
+fn parse_input(input: &str) -> String {
+    Extractor::new(Vocabulary::english()).sum(input).to_string()
 }
 
 #[cfg(test)]
@@ -114,4 +62,3 @@ zoneight234
         assert_eq!(output, "281".to_string());
     }
 }
-
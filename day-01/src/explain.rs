@@ -0,0 +1,77 @@
+use std::fmt::Write;
+
+use crate::{Extractor, Token};
+
+/// How the calibration value of one line came about.
+pub struct Explanation<'a> {
+    pub number: usize,
+    pub line: &'a str,
+    /// `None` for lines without any token, which count as 0.
+    pub first_last: Option<(Token, Token)>,
+    pub value: u32,
+}
+
+pub fn explain<'a>(extractor: &Extractor, input: &'a str) -> Vec<Explanation<'a>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| Explanation {
+            number: idx + 1,
+            line,
+            first_last: extractor.first_last(line),
+            value: extractor.value(line),
+        })
+        .collect()
+}
+
+pub fn format_explanations(explanations: &[Explanation]) -> String {
+    let text = |line: &str, token: Token| line[token.offset..token.offset + token.len].to_string();
+    let mut output = String::new();
+    for explanation in explanations {
+        let line = explanation.line;
+        match explanation.first_last {
+            Some((first, last)) => writeln!(
+                output,
+                "{:>4}: {:?} first {:?} at {}, last {:?} at {} -> {}",
+                explanation.number,
+                line,
+                text(line, first),
+                first.offset,
+                text(line, last),
+                last.offset,
+                explanation.value
+            ),
+            None => writeln!(
+                output,
+                "{:>4}: {:?} warning: no digits, counts as 0",
+                explanation.number, line
+            ),
+        }
+        .unwrap();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vocabulary;
+
+    #[test]
+    fn explain_both_modes() {
+        let input = "\ntreb7uchet\nzoneight234\n";
+        let digits = explain(&Extractor::new(Vocabulary::digits()), input);
+        assert_eq!(
+            format_explanations(&digits),
+            "   1: \"\" warning: no digits, counts as 0
+   2: \"treb7uchet\" first \"7\" at 4, last \"7\" at 4 -> 77
+   3: \"zoneight234\" first \"2\" at 8, last \"4\" at 10 -> 24
+"
+        );
+        let words = explain(&Extractor::new(Vocabulary::english()), input);
+        assert_eq!(
+            format_explanations(&words[2..]),
+            "   3: \"zoneight234\" first \"one\" at 1, last \"4\" at 10 -> 14\n"
+        );
+    }
+}
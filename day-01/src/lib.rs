@@ -0,0 +1,404 @@
+use std::fmt;
+
+use scanner::Scanner;
+
+pub mod explain;
+pub mod scanner;
+
+const LANGUAGES: [(&str, [&str; 9]); 4] = [
+    (
+        "english",
+        [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ],
+    ),
+    (
+        "german",
+        [
+            "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ],
+    ),
+    (
+        "french",
+        [
+            "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+        ],
+    ),
+    (
+        "spanish",
+        [
+            "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+        ],
+    ),
+];
+
+/// A digit or word found in a line, with its byte offset and length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Token {
+    pub offset: usize,
+    pub len: usize,
+    pub value: u32,
+}
+
+/// The words that stand for numbers, and whether plain digits count too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vocabulary {
+    words: Vec<(String, u32)>,
+    digits: bool,
+    ignore_case: bool,
+}
+
+impl Vocabulary {
+    /// Only the digits `0` to `9`, as in part 1.
+    pub fn digits() -> Self {
+        Vocabulary {
+            words: Vec::new(),
+            digits: true,
+            ignore_case: false,
+        }
+    }
+
+    /// The digits and the English words `one` to `nine`, as in part 2.
+    pub fn english() -> Self {
+        Self::digits()
+            .with_language("english")
+            .expect("English is built in")
+    }
+
+    /// Adds the words for one to nine of a built in language.
+    pub fn with_language(mut self, language: &str) -> Option<Self> {
+        let (_, words) = LANGUAGES.iter().find(|(name, _)| *name == language)?;
+        for (value, word) in (1..).zip(words) {
+            self = self.with_word(word, value);
+        }
+        Some(self)
+    }
+
+    pub fn with_word(mut self, word: &str, value: u32) -> Self {
+        let word = if self.ignore_case {
+            fold_case(word)
+        } else {
+            word.to_string()
+        };
+        self.words.push((word, value));
+        self
+    }
+
+    pub fn with_digits(mut self, digits: bool) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    pub fn with_ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        if ignore_case {
+            for (word, _) in self.words.iter_mut() {
+                *word = fold_case(word);
+            }
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && !self.digits
+    }
+
+    /// Every word and digit in the line, ordered by offset. Longer tokens come
+    /// first where two start at the same offset.
+    ///
+    /// This searches the line once per word, it is kept to check and
+    /// benchmark the `Scanner` against.
+    pub fn match_tokens(&self, line: &str) -> Vec<Token> {
+        let folded;
+        let haystack = if self.ignore_case {
+            folded = fold_case(line);
+            folded.as_str()
+        } else {
+            line
+        };
+
+        let mut tokens = Vec::new();
+        for (word, value) in &self.words {
+            tokens.extend(
+                haystack
+                    .match_indices(word.as_str())
+                    .map(|(offset, _)| Token {
+                        offset,
+                        len: word.len(),
+                        value: *value,
+                    }),
+            );
+        }
+        if self.digits {
+            tokens.extend(line.char_indices().filter_map(|(offset, character)| {
+                character.to_digit(10).map(|value| Token {
+                    offset,
+                    len: 1,
+                    value,
+                })
+            }));
+        }
+
+        tokens.sort_unstable_by_key(|token| (token.offset, usize::MAX - token.len));
+        tokens
+    }
+}
+
+/// Lowercases every character that keeps its length in bytes, so offsets into
+/// the folded line are offsets into the original one.
+fn fold_case(line: &str) -> String {
+    line.chars()
+        .map(|character| {
+            let mut lower = character.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(lower), None) if lower.len_utf8() == character.len_utf8() => lower,
+                _ => character,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combine {
+    /// Ten times the first value plus the last value.
+    Tens,
+    /// The decimal digits of the first value followed by those of the last,
+    /// so `12` and `3` give `123`.
+    Concat,
+}
+
+/// How the first and last token of a line become its calibration value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rules {
+    /// Whether tokens may share letters, as in `eightwo`. Without overlaps the
+    /// line is split from the left and the longest token wins at each offset.
+    pub overlapping: bool,
+    pub combine: Combine,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            overlapping: true,
+            combine: Combine::Tens,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    UnknownKey(String),
+    InvalidLine(String),
+    UnknownLanguage(String),
+    EmptyVocabulary,
+    /// A word for a number above nine with `combine = tens`.
+    MultiDigitWord(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => write!(f, "Unknown key: {}", key),
+            ConfigError::InvalidLine(line) => write!(f, "Invalid line: {}", line),
+            ConfigError::UnknownLanguage(language) => {
+                write!(f, "Unknown language: {}", language)
+            }
+            ConfigError::EmptyVocabulary => write!(f, "No words and no digits to look for"),
+            ConfigError::MultiDigitWord(word) => write!(
+                f,
+                "Word {} stands for more than one digit, which needs combine = concat",
+                word
+            ),
+        }
+    }
+}
+
+pub struct Extractor {
+    scanner: Scanner,
+    pub rules: Rules,
+}
+
+impl Extractor {
+    pub fn new(vocabulary: Vocabulary) -> Self {
+        Self::with_rules(vocabulary, Rules::default())
+    }
+
+    pub fn with_rules(vocabulary: Vocabulary, rules: Rules) -> Self {
+        Extractor {
+            scanner: Scanner::new(&vocabulary),
+            rules,
+        }
+    }
+
+    /// Reads `key = value` lines, `#` starts a comment:
+    ///
+    /// ```text
+    /// language = german      # one to nine, may be given more than once
+    /// word = null 0          # any word, values above 9 need concat
+    /// digits = true
+    /// ignore_case = true
+    /// overlapping = false
+    /// combine = concat       # or tens
+    /// ```
+    pub fn from_config(input: &str) -> Result<Self, ConfigError> {
+        let mut vocabulary = Vocabulary::digits();
+        let mut rules = Rules::default();
+        for line in input.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || ConfigError::InvalidLine(line.to_string());
+            let flag = |value: &str| value.parse::<bool>().map_err(|_| invalid());
+            let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+            let value = value.trim();
+            match key.trim() {
+                "language" => {
+                    vocabulary = vocabulary
+                        .with_language(value)
+                        .ok_or_else(|| ConfigError::UnknownLanguage(value.to_string()))?
+                }
+                "word" => {
+                    let (word, number) = value.split_once(' ').ok_or_else(invalid)?;
+                    let number = number.trim().parse().map_err(|_| invalid())?;
+                    vocabulary = vocabulary.with_word(word, number);
+                }
+                "digits" => vocabulary = vocabulary.with_digits(flag(value)?),
+                "ignore_case" => vocabulary = vocabulary.with_ignore_case(flag(value)?),
+                "overlapping" => rules.overlapping = flag(value)?,
+                "combine" => {
+                    rules.combine = match value {
+                        "tens" => Combine::Tens,
+                        "concat" => Combine::Concat,
+                        _ => return Err(invalid()),
+                    }
+                }
+                other => return Err(ConfigError::UnknownKey(other.to_string())),
+            }
+        }
+        if vocabulary.is_empty() {
+            return Err(ConfigError::EmptyVocabulary);
+        }
+        if rules.combine == Combine::Tens {
+            if let Some((word, _)) = vocabulary.words.iter().find(|(_, value)| *value > 9) {
+                return Err(ConfigError::MultiDigitWord(word.clone()));
+            }
+        }
+        Ok(Self::with_rules(vocabulary, rules))
+    }
+
+    /// The tokens the rules pick from, in order.
+    pub fn tokens(&self, line: &str) -> Vec<Token> {
+        let tokens = self.scanner.tokens(line);
+        if self.rules.overlapping {
+            return tokens;
+        }
+        let mut end = 0;
+        tokens
+            .into_iter()
+            .filter(|token| {
+                let free = token.offset >= end;
+                if free {
+                    end = token.offset + token.len;
+                }
+                free
+            })
+            .collect()
+    }
+
+    pub fn first_last(&self, line: &str) -> Option<(Token, Token)> {
+        if self.rules.overlapping {
+            return Some((self.scanner.first(line)?, self.scanner.last(line)?));
+        }
+        // Without overlaps the tokens at the end depend on how the line was
+        // split from the start
+        let tokens = self.tokens(line);
+        let first = *tokens.first()?;
+        // The longest token at the last offset
+        let last_offset = tokens.last()?.offset;
+        let last = *tokens.iter().find(|token| token.offset == last_offset)?;
+        Some((first, last))
+    }
+
+    /// The calibration value of the line, 0 if there is no token in it.
+    pub fn value(&self, line: &str) -> u32 {
+        match self.first_last(line) {
+            None => 0,
+            Some((first, last)) => match self.rules.combine {
+                Combine::Tens => first.value * 10 + last.value,
+                Combine::Concat => format!("{}{}", first.value, last.value)
+                    .parse()
+                    .expect("Two numbers should make a number"),
+            },
+        }
+    }
+
+    pub fn sum(&self, input: &str) -> u32 {
+        input.lines().map(|line| self.value(line)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_vocabulary() {
+        let extractor = Extractor::from_config(
+            "language = german
+word = null 0
+word = zwölf 12
+ignore_case = true
+combine = concat
+",
+        )
+        .unwrap();
+        assert_eq!(extractor.value("xNULLabc7"), 7);
+        assert_eq!(extractor.value("Zwölfzwei"), 122);
+        assert_eq!(extractor.value("1fünfundZWÖLF"), 112);
+        assert_eq!(extractor.value("kein wert"), 0);
+    }
+
+    #[test]
+    fn overlapping_tokens() {
+        let mut extractor = Extractor::new(Vocabulary::english());
+        assert_eq!(extractor.value("eightwo"), 82);
+        extractor.rules.overlapping = false;
+        assert_eq!(extractor.value("eightwo"), 88);
+        assert_eq!(extractor.value("xtwone3"), 23);
+    }
+
+    #[test]
+    fn longest_token_wins() {
+        let extractor =
+            Extractor::from_config("language = english\nword = eighteen 18\ncombine = concat")
+                .unwrap();
+        let (first, last) = extractor.first_last("eighteen").unwrap();
+        assert_eq!((first.value, last.value), (18, 18));
+        assert_eq!(extractor.value("1eighteen"), 118);
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert_eq!(
+            Extractor::from_config("language = klingon").err(),
+            Some(ConfigError::UnknownLanguage("klingon".to_string()))
+        );
+        assert_eq!(
+            Extractor::from_config("digits = false").err(),
+            Some(ConfigError::EmptyVocabulary)
+        );
+        assert_eq!(
+            Extractor::from_config("word = ten").err(),
+            Some(ConfigError::InvalidLine("word = ten".to_string()))
+        );
+        assert_eq!(
+            Extractor::from_config("words = ten 10").err(),
+            Some(ConfigError::UnknownKey("words".to_string()))
+        );
+        assert_eq!(
+            Extractor::from_config("word = eighteen 18").err(),
+            Some(ConfigError::MultiDigitWord("eighteen".to_string()))
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use crate::{fold_case, Token, Vocabulary};
+
+/// Aho-Corasick automaton over bytes, with every transition filled in so each
+/// byte of the line costs one table lookup.
+struct Automaton {
+    transitions: Vec<[u32; 256]>,
+    /// The patterns ending in each state, longest first.
+    matches: Vec<Vec<usize>>,
+}
+
+impl Automaton {
+    fn new(patterns: &[Vec<u8>]) -> Self {
+        // The root is never a child, so 0 marks a missing child in the trie
+        let mut transitions = vec![[0u32; 256]];
+        let mut matches = vec![Vec::new()];
+        for (idx, pattern) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for &byte in pattern {
+                if transitions[state][byte as usize] == 0 {
+                    transitions[state][byte as usize] = transitions.len() as u32;
+                    transitions.push([0; 256]);
+                    matches.push(Vec::new());
+                }
+                state = transitions[state][byte as usize] as usize;
+            }
+            matches[state].push(idx);
+        }
+
+        let mut fail = vec![0; transitions.len()];
+        let mut queue: VecDeque<usize> = transitions[0]
+            .iter()
+            .filter(|&&next| next != 0)
+            .map(|&next| next as usize)
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            let inherited = matches[fail[state]].clone();
+            matches[state].extend(inherited);
+            let fallback = transitions[fail[state]];
+            for (byte, next) in transitions[state].iter_mut().enumerate() {
+                if *next == 0 {
+                    *next = fallback[byte];
+                } else {
+                    fail[*next as usize] = fallback[byte] as usize;
+                    queue.push_back(*next as usize);
+                }
+            }
+        }
+        for state_matches in matches.iter_mut() {
+            state_matches.sort_unstable_by_key(|&idx| usize::MAX - patterns[idx].len());
+        }
+
+        Automaton {
+            transitions,
+            matches,
+        }
+    }
+
+    fn next(&self, state: usize, byte: u8) -> usize {
+        self.transitions[state][byte as usize] as usize
+    }
+}
+
+/// Finds the tokens of a vocabulary in one pass over a line. The first token
+/// comes from a scan from the left and the last one from a scan from the
+/// right over the reversed words, so neither has to look at the whole line.
+pub struct Scanner {
+    forward: Automaton,
+    backward: Automaton,
+    /// Length and value of every pattern.
+    patterns: Vec<(usize, u32)>,
+    longest: usize,
+    ignore_case: bool,
+}
+
+impl Scanner {
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        let mut words: Vec<(String, u32)> = vocabulary
+            .words
+            .iter()
+            .filter(|(word, _)| !word.is_empty())
+            .cloned()
+            .collect();
+        if vocabulary.digits {
+            words.extend((0..10).map(|digit| (digit.to_string(), digit)));
+        }
+        let forward: Vec<Vec<u8>> = words
+            .iter()
+            .map(|(word, _)| word.bytes().collect())
+            .collect();
+        let backward: Vec<Vec<u8>> = words
+            .iter()
+            .map(|(word, _)| word.bytes().rev().collect())
+            .collect();
+
+        Scanner {
+            forward: Automaton::new(&forward),
+            backward: Automaton::new(&backward),
+            longest: words.iter().map(|(word, _)| word.len()).max().unwrap_or(0),
+            patterns: words
+                .iter()
+                .map(|(word, value)| (word.len(), *value))
+                .collect(),
+            ignore_case: vocabulary.ignore_case,
+        }
+    }
+
+    fn haystack<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        if self.ignore_case {
+            Cow::Owned(fold_case(line))
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+
+    fn token(&self, pattern: usize, offset: usize) -> Token {
+        let (len, value) = self.patterns[pattern];
+        Token { offset, len, value }
+    }
+
+    /// Every token in the line, ordered by offset. Longer tokens come first
+    /// where two start at the same offset.
+    pub fn tokens(&self, line: &str) -> Vec<Token> {
+        let haystack = self.haystack(line);
+        let mut tokens = Vec::new();
+        let mut state = 0;
+        for (idx, byte) in haystack.bytes().enumerate() {
+            state = self.forward.next(state, byte);
+            for &pattern in &self.forward.matches[state] {
+                let offset = idx + 1 - self.patterns[pattern].0;
+                tokens.push(self.token(pattern, offset));
+            }
+        }
+        tokens.sort_unstable_by_key(|token| (token.offset, usize::MAX - token.len));
+        tokens
+    }
+
+    /// The token starting first, the longest one if several start there.
+    pub fn first(&self, line: &str) -> Option<Token> {
+        let haystack = self.haystack(line);
+        let mut first: Option<Token> = None;
+        let mut state = 0;
+        for (idx, byte) in haystack.bytes().enumerate() {
+            // Tokens ending from here on start after the one found
+            if first.is_some_and(|token| idx >= token.offset + self.longest) {
+                break;
+            }
+            state = self.forward.next(state, byte);
+            for &pattern in &self.forward.matches[state] {
+                let token = self.token(pattern, idx + 1 - self.patterns[pattern].0);
+                if first.is_none_or(|first| {
+                    (token.offset, usize::MAX - token.len) < (first.offset, usize::MAX - first.len)
+                }) {
+                    first = Some(token);
+                }
+            }
+        }
+        first
+    }
+
+    /// The token starting last, the longest one if several start there.
+    pub fn last(&self, line: &str) -> Option<Token> {
+        let haystack = self.haystack(line);
+        let mut state = 0;
+        for (idx, byte) in haystack.bytes().enumerate().rev() {
+            state = self.backward.next(state, byte);
+            // Reversed, every match found here starts at `idx`, longest first
+            if let Some(&pattern) = self.backward.matches[state].first() {
+                return Some(self.token(pattern, idx));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_words() {
+        let scanner = Scanner::new(&Vocabulary::english());
+        let line = "xeightwone7";
+        assert_eq!(
+            scanner.tokens(line),
+            Vocabulary::english().match_tokens(line)
+        );
+        assert_eq!(
+            scanner.first(line),
+            Some(Token {
+                offset: 1,
+                len: 5,
+                value: 8
+            })
+        );
+        assert_eq!(
+            scanner.last(line),
+            Some(Token {
+                offset: 10,
+                len: 1,
+                value: 7
+            })
+        );
+        assert_eq!(scanner.first("abc"), None);
+        assert_eq!(scanner.last(""), None);
+    }
+
+    #[test]
+    fn longest_at_same_offset() {
+        let vocabulary = Vocabulary::english().with_word("eighteen", 18);
+        let scanner = Scanner::new(&vocabulary);
+        assert_eq!(scanner.first("eighteen").map(|token| token.value), Some(18));
+        assert_eq!(scanner.last("eighteen").map(|token| token.value), Some(18));
+        // `eight` is found first, but `seventeen` starts before it
+        let vocabulary = vocabulary.with_word("seventeen", 17);
+        let scanner = Scanner::new(&vocabulary);
+        assert_eq!(
+            scanner.first("seventeight").map(|token| token.value),
+            Some(7)
+        );
+        assert_eq!(
+            scanner.first("xseventeen").map(|token| token.value),
+            Some(17)
+        );
+    }
+
+    #[test]
+    fn agrees_with_match_indices() {
+        let vocabulary = Vocabulary::english()
+            .with_language("german")
+            .unwrap()
+            .with_word("zero", 0)
+            .with_ignore_case(true);
+        let scanner = Scanner::new(&vocabulary);
+        let letters = [
+            "o", "n", "e", "t", "w", "i", "g", "h", "1", "x", "ü", "F", "Z",
+        ];
+        let mut seed = 7u64;
+        for _ in 0..2000 {
+            let mut line = String::new();
+            for _ in 0..(seed % 24) {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                line.push_str(letters[(seed >> 33) as usize % letters.len()]);
+            }
+            let tokens = vocabulary.match_tokens(&line);
+            assert_eq!(scanner.tokens(&line), tokens, "{}", line);
+            assert_eq!(scanner.first(&line), tokens.first().copied(), "{}", line);
+            let last = tokens
+                .iter()
+                .find(|token| Some(token.offset) == tokens.last().map(|last| last.offset));
+            assert_eq!(scanner.last(&line), last.copied(), "{}", line);
+        }
+    }
+}
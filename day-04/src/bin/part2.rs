@@ -1,35 +1,51 @@
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+
 fn main() {
-    println!("Part 2");
     let input = include_str!("./input2.txt");
-    let result = solve(input);
-    println!("Result = {}", result);
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            println!("Part 2");
+            let result = solve(input);
+            println!("Result = {}", result);
+        }
+        ["--report"] | ["--report", "table"] => print!("{}", build_report(input).to_table()),
+        ["--report", "json"] => println!("{}", build_report(input).to_json()),
+        _ => panic!("Usage: part2 [--report [table|json]]"),
+    }
 }
 
 struct Card {
-    winning: Vec<u16>,
-    having: Vec<u16>,
-    copies: u32,
+    matched: usize,
+    copies: u64,
 }
 
 impl Card {
     pub fn from(winning: Vec<u16>, having: Vec<u16>) -> Self {
-        Self {
-            winning,
-            having,
-            copies: 1,
-        }
+        let winning: HashSet<u16> = HashSet::from_iter(winning);
+        let matched = having
+            .iter()
+            .filter(|number| winning.contains(number))
+            .count();
+        Self { matched, copies: 1 }
     }
-    pub fn get_matched(&self) -> u32 {
-        let mut matched = 0;
-        for number in &self.having {
-            if self.winning.contains(number) {
-                matched += 1;
-            }
+    pub fn get_matched(&self) -> usize {
+        self.matched
+    }
+    pub fn get_points_worth(&self) -> u64 {
+        match self.matched {
+            0 => 0,
+            matched => 1u64
+                .checked_shl(matched as u32 - 1)
+                .expect("Too many points for u64"),
         }
-        matched
     }
-    pub fn incr_copies(&mut self) {
-        self.copies += 1;
+    pub fn add_copies(&mut self, copies: u64) {
+        self.copies = self
+            .copies
+            .checked_add(copies)
+            .expect("Too many copies for u64");
     }
 }
 
@@ -62,27 +78,139 @@ fn parse_cards(input: &str) -> Vec<Card> {
     cards
 }
 
-fn calculate_copies(cards: &mut Vec<Card>) {
+// Every copy of a card wins the same following cards, so all copies are
+// handed on at once
+fn calculate_copies(cards: &mut [Card]) {
     for i in 0..cards.len() {
-        for _ in 0..cards[i].copies {
-            for j in 1..cards[i].get_matched() as usize + 1 {
-                cards.get_mut(i + j).unwrap().incr_copies();
-            }
+        let (copies, matched) = (cards[i].copies, cards[i].get_matched());
+        for card in cards.iter_mut().skip(i + 1).take(matched) {
+            card.add_copies(copies);
         }
     }
 }
 
-fn sum_card_copies(cards: &Vec<Card>) -> u32 {
-    let mut copies = 0;
+fn sum_card_copies(cards: &[Card]) -> u64 {
+    let mut copies: u64 = 0;
     for card in cards {
-        copies += card.copies;
+        copies = copies
+            .checked_add(card.copies)
+            .expect("Too many copies for u64");
     }
     copies
 }
 
+// Report
+
+struct CardReport {
+    card: usize,
+    matched: usize,
+    points: u64,
+    copies: u64,
+    /// Earlier cards that won copies of this one, with how many they won.
+    won_from: Vec<(usize, u64)>,
+}
+
+struct Report {
+    cards: Vec<CardReport>,
+    /// How many cards have a certain number of matches.
+    match_distribution: BTreeMap<usize, usize>,
+}
+
+fn build_report(input: &str) -> Report {
+    let mut cards = parse_cards(input);
+    calculate_copies(&mut cards);
+
+    let mut won_from = vec![Vec::new(); cards.len()];
+    for (i, card) in cards.iter().enumerate() {
+        for target in won_from.iter_mut().skip(i + 1).take(card.get_matched()) {
+            target.push((i + 1, card.copies));
+        }
+    }
+    let mut match_distribution = BTreeMap::new();
+    for card in &cards {
+        *match_distribution.entry(card.get_matched()).or_insert(0) += 1;
+    }
+    let cards = cards
+        .iter()
+        .zip(won_from)
+        .enumerate()
+        .map(|(i, (card, won_from))| CardReport {
+            card: i + 1,
+            matched: card.get_matched(),
+            points: card.get_points_worth(),
+            copies: card.copies,
+            won_from,
+        })
+        .collect();
+    Report {
+        cards,
+        match_distribution,
+    }
+}
+
+impl Report {
+    fn to_table(&self) -> String {
+        let mut table = String::from("Card | Matches | Points | Copies | Won from\n");
+        for card in &self.cards {
+            let won_from: Vec<String> = card
+                .won_from
+                .iter()
+                .map(|(source, copies)| format!("{source} ({copies})"))
+                .collect();
+            let row = format!(
+                "{:>4} | {:>7} | {:>6} | {:>6} | {}",
+                card.card,
+                card.matched,
+                card.points,
+                card.copies,
+                won_from.join(", ")
+            );
+            table.push_str(row.trim_end());
+            table.push('\n');
+        }
+        table.push_str("\nMatches | Cards\n");
+        for (matched, count) in &self.match_distribution {
+            table.push_str(&format!("{matched:>7} | {count:>5}\n"));
+        }
+        table
+    }
+
+    fn to_json(&self) -> String {
+        let cards: Vec<String> = self
+            .cards
+            .iter()
+            .map(|card| {
+                let won_from: Vec<String> = card
+                    .won_from
+                    .iter()
+                    .map(|(source, copies)| format!(r#"{{"card":{source},"copies":{copies}}}"#))
+                    .collect();
+                format!(
+                    r#"{{"card":{},"matches":{},"points":{},"copies":{},"won_from":[{}]}}"#,
+                    card.card,
+                    card.matched,
+                    card.points,
+                    card.copies,
+                    won_from.join(",")
+                )
+            })
+            .collect();
+        let distribution: Vec<String> = self
+            .match_distribution
+            .iter()
+            .map(|(matched, count)| format!(r#""{matched}":{count}"#))
+            .collect();
+        format!(
+            r#"{{"cards":[{}],"match_distribution":{{{}}}}}"#,
+            cards.join(","),
+            distribution.join(",")
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::solve;
+    use crate::{build_report, solve, Card};
 
     #[test]
     fn example_input() {
@@ -96,5 +224,54 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
         let output = solve(input);
         assert_eq!(output, "30".to_string());
     }
-}
 
+    #[test]
+    fn many_copies() {
+        // Every card wins all following ones, so card n ends up with 2^(n-1) copies
+        let mut input = String::new();
+        for card in 1..=40 {
+            input.push_str(&format!("Card {card}: 1 |{}\n", " 1".repeat(40)));
+        }
+        let output = solve(&input);
+        assert_eq!(output, ((1u64 << 40) - 1).to_string());
+    }
+
+    #[test]
+    fn points_up_to_u64() {
+        let card = Card::from(vec![1], vec![1; 64]);
+        assert_eq!(card.get_points_worth(), 1 << 63);
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many points for u64")]
+    fn too_many_points() {
+        Card::from(vec![1], vec![1; 65]).get_points_worth();
+    }
+
+    #[test]
+    fn report() {
+        let input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+        let report = build_report(input);
+        let card = &report.cards[3];
+        assert_eq!((card.matched, card.points, card.copies), (1, 1, 8));
+        assert_eq!(card.won_from, vec![(1, 1), (2, 2), (3, 4)]);
+
+        let table = report.to_table();
+        assert!(table.contains("   4 |       1 |      1 |      8 | 1 (1), 2 (2), 3 (4)\n"));
+        assert!(
+            table.ends_with("      0 |     2\n      1 |     1\n      2 |     2\n      4 |     1\n")
+        );
+
+        let json = report.to_json();
+        assert!(json.starts_with(
+            r#"{"cards":[{"card":1,"matches":4,"points":8,"copies":1,"won_from":[]},"#
+        ));
+        assert!(json.ends_with(r#""match_distribution":{"0":2,"1":1,"2":2,"4":1}}"#));
+    }
+}
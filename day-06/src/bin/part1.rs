@@ -1,3 +1,5 @@
+use day_06::{parse_races, Boat};
+
 fn main() {
     println!("Part 1");
     let input = include_str!("../../input1.txt");
@@ -5,61 +7,14 @@ fn main() {
     println!("Result = {}", result);
 }
 
-struct Game {
-    time: u32,
-    record: u32,
-}
-
 fn solve(input: &str) -> String {
-    let games = parse_games(input);
-    get_possibilities_combined(&games).to_string()
-}
-
-fn parse_games(input: &str) -> Vec<Game> {
-    let mut result = Vec::new();
-    let mut lines = input.lines();
-    let mut times = lines
-        .next()
-        .expect("Not in the right format")
-        .split_ascii_whitespace()
-        .skip(1);
-    let mut distances = lines
-        .next()
-        .expect("Not in the right format")
-        .split_ascii_whitespace()
-        .skip(1);
-
-    while let (Some(time), Some(distance)) = (times.next(), distances.next()) {
-        result.push(Game {
-            time: time.parse().expect("Should be a number"),
-            record: distance.parse().expect("Should be a number"),
-        })
-    }
-    result
-}
-
-fn possibilities_break_record(Game { time, record }: &Game) -> u32 {
-    let mut poss = 0;
-    let mut passed = false;
-    for i in 0..*time {
-        let speed = i;
-        let left = time - i;
-        if speed * left > *record {
-            poss += 1;
-            passed = true;
-        } else if passed {
-            break;
-        }
-    }
-    poss
-}
-
-fn get_possibilities_combined(games: &[Game]) -> u32 {
-    let mut result = 1;
-    for game in games {
-        result *= possibilities_break_record(game);
-    }
-    result
+    let races = parse_races(input);
+    let boat = Boat::default();
+    races
+        .iter()
+        .map(|race| boat.winning_hold_count(race))
+        .product::<u64>()
+        .to_string()
 }
 
 #[cfg(test)]
@@ -75,4 +30,3 @@ Distance:  9  40  200
         assert_eq!(output, "288".to_string());
     }
 }
-
@@ -1,4 +1,4 @@
-use std::str::Lines;
+use day_06::{parse_kerned_race, Boat};
 
 fn main() {
     println!("Part 2");
@@ -7,53 +7,9 @@ fn main() {
     println!("Result = {}", result);
 }
 
-#[derive(Default)]
-struct Game {
-    time: u64,
-    record: u64,
-}
-
 fn solve(input: &str) -> String {
-    let games = parse_games(input);
-    possibilities_break_record(&games).to_string()
-}
-
-fn parse_games(input: &str) -> Game {
-    let mut lines = input.lines();
-    let time: u64 = parse_big_number(&mut lines);
-    let record: u64 = parse_big_number(&mut lines);
-
-    Game { time, record }
-}
-
-fn parse_big_number(input: &mut Lines) -> u64 {
-    String::from(
-        input
-            .next()
-            .expect("Not in the right format")
-            .split(':')
-            .skip(1)
-            .collect::<Vec<_>>()[0],
-    )
-    .replace(' ', "")
-    .parse()
-    .expect("Should be a number")
-}
-
-fn possibilities_break_record(Game { time, record }: &Game) -> u64 {
-    let mut poss = 0;
-    let mut passed = false;
-    for i in 0..*time + 1 {
-        let speed = i;
-        let left = time - i;
-        if speed * left > *record {
-            poss += 1;
-            passed = true;
-        } else if passed {
-            break;
-        }
-    }
-    poss
+    let race = parse_kerned_race(input);
+    Boat::default().winning_hold_count(&race).to_string()
 }
 
 #[cfg(test)]
@@ -69,4 +25,3 @@ Distance:  9  40  200
         assert_eq!(output, "71503".to_string());
     }
 }
-
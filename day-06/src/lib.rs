@@ -0,0 +1,214 @@
+use std::ops::RangeInclusive;
+
+// Logic
+
+pub struct Race {
+    pub time: u64,
+    pub record: u64,
+}
+
+/// A toy boat that gains `acceleration` mm/ms of speed for every millisecond
+/// the button is held, optionally capped at `max_speed`.
+pub struct Boat {
+    acceleration: u64,
+    max_speed: Option<u64>,
+}
+
+impl Default for Boat {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl Boat {
+    pub fn new(acceleration: u64) -> Self {
+        Self {
+            acceleration,
+            max_speed: None,
+        }
+    }
+
+    pub fn with_max_speed(mut self, max_speed: u64) -> Self {
+        self.max_speed = Some(max_speed);
+        self
+    }
+
+    pub fn speed(&self, hold: u64) -> u128 {
+        let speed = hold as u128 * self.acceleration as u128;
+        match self.max_speed {
+            Some(max_speed) => speed.min(max_speed as u128),
+            None => speed,
+        }
+    }
+
+    pub fn distance(&self, race: &Race, hold: u64) -> u128 {
+        if hold > race.time {
+            return 0;
+        }
+        self.speed(hold) * (race.time - hold) as u128
+    }
+
+    /// Hold time with the largest distance, the shortest one if several tie.
+    ///
+    /// The distance first grows like a parabola until the speed cap is hit and
+    /// only shrinks afterwards, so the best hold is either the top of the
+    /// parabola or one of the two holds around the cap.
+    pub fn optimal_hold(&self, race: &Race) -> u64 {
+        let half = race.time / 2;
+        let mut candidates = vec![half, race.time - half];
+        if let Some(max_speed) = self.max_speed {
+            let last_uncapped = max_speed.checked_div(self.acceleration).unwrap_or(u64::MAX);
+            candidates = candidates
+                .into_iter()
+                .map(|hold| hold.min(last_uncapped))
+                .collect();
+            candidates.push(last_uncapped.saturating_add(1).min(race.time));
+        }
+        candidates.sort();
+        let mut best = candidates[0];
+        for &hold in candidates.iter().skip(1) {
+            if self.distance(race, hold) > self.distance(race, best) {
+                best = hold;
+            }
+        }
+        best
+    }
+
+    pub fn best_distance(&self, race: &Race) -> u128 {
+        self.distance(race, self.optimal_hold(race))
+    }
+
+    /// All hold times that beat the record, `None` if the record can't be beaten.
+    pub fn winning_holds(&self, race: &Race) -> Option<RangeInclusive<u64>> {
+        let record = race.record as u128;
+        let optimal = self.optimal_hold(race);
+        if self.distance(race, optimal) <= record {
+            return None;
+        }
+
+        // Distance is non-decreasing up to the optimum and non-increasing after
+        let (mut low, mut high) = (0, optimal);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.distance(race, mid) > record {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        let first = low;
+
+        let (mut low, mut high) = (optimal, race.time);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if self.distance(race, mid) > record {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        let last = low;
+
+        Some(first..=last)
+    }
+
+    pub fn winning_hold_count(&self, race: &Race) -> u64 {
+        match self.winning_holds(race) {
+            Some(holds) => holds.end() - holds.start() + 1,
+            None => 0,
+        }
+    }
+}
+
+// Parser
+
+/// One race per column, as in part 1.
+pub fn parse_races(input: &str) -> Vec<Race> {
+    let (times, records) = parse_sheet(input);
+    times
+        .iter()
+        .zip(records.iter())
+        .map(|(time, record)| Race {
+            time: time.parse().expect("Should be a number"),
+            record: record.parse().expect("Should be a number"),
+        })
+        .collect()
+}
+
+/// A single race with the columns joined together, as in part 2.
+pub fn parse_kerned_race(input: &str) -> Race {
+    let (times, records) = parse_sheet(input);
+    Race {
+        time: times.concat().parse().expect("Should be a number"),
+        record: records.concat().parse().expect("Should be a number"),
+    }
+}
+
+fn parse_sheet(input: &str) -> (Vec<&str>, Vec<&str>) {
+    let mut lines = input.lines().filter(|line| !line.trim().is_empty());
+    let mut parse_line = |name: &str| -> Vec<&str> {
+        let line = lines.next().expect("Not in the right format");
+        let (label, numbers) = line.split_once(':').expect("Not in the right format");
+        assert_eq!(label.trim(), name, "Not in the right format");
+        numbers.split_ascii_whitespace().collect()
+    };
+    let times = parse_line("Time");
+    let records = parse_line("Distance");
+    (times, records)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "Time:      7  15   30
+Distance:  9  40  200
+";
+
+    #[test]
+    fn parse_both_layouts() {
+        let races = parse_races(INPUT);
+        assert_eq!(races.len(), 3);
+        assert_eq!((races[2].time, races[2].record), (30, 200));
+
+        let race = parse_kerned_race(INPUT);
+        assert_eq!((race.time, race.record), (71530, 940200));
+    }
+
+    #[test]
+    fn default_boat_queries() {
+        let boat = Boat::default();
+        let race = Race { time: 7, record: 9 };
+        assert_eq!(boat.optimal_hold(&race), 3);
+        assert_eq!(boat.best_distance(&race), 12);
+        assert_eq!(boat.winning_holds(&race), Some(2..=5));
+        assert_eq!(boat.winning_hold_count(&race), 4);
+
+        let race = Race {
+            time: 30,
+            record: 200,
+        };
+        assert_eq!(boat.winning_holds(&race), Some(11..=19));
+    }
+
+    #[test]
+    fn configured_boat_queries() {
+        let boat = Boat::new(2).with_max_speed(6);
+        let race = Race {
+            time: 10,
+            record: 30,
+        };
+        // Speed is capped from hold 3 on
+        assert_eq!(boat.optimal_hold(&race), 3);
+        assert_eq!(boat.best_distance(&race), 42);
+        assert_eq!(boat.winning_holds(&race), Some(2..=4));
+
+        let race = Race {
+            time: 10,
+            record: 42,
+        };
+        assert_eq!(boat.winning_holds(&race), None);
+        assert_eq!(boat.winning_hold_count(&race), 0);
+    }
+}
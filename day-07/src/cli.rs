@@ -0,0 +1,53 @@
+use std::fs;
+
+use crate::standings::{
+    format_standings, format_what_if, sort_standings, standings, what_if, Column,
+};
+use crate::{total_winnings, Ruleset};
+
+/// Handles the arguments both parts take and returns the total winnings.
+/// `default` is the ruleset of the part, used unless `--rules` is given.
+pub fn run(
+    bin: &str,
+    input: &str,
+    default: Ruleset,
+    mut args: impl Iterator<Item = String>,
+) -> u64 {
+    let usage = format!(
+        "Usage: {} [--rules <file>] [--standings [--sort <column>] [--desc]] [--what-if]",
+        bin
+    );
+    let mut ruleset = default;
+    let (mut show_standings, mut sort, mut descending) = (false, Column::Rank, false);
+    let mut show_what_if = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rules" => {
+                let config = fs::read_to_string(args.next().expect(&usage))
+                    .expect("Could not read the rules");
+                ruleset = Ruleset::from_config(&config).unwrap_or_else(|err| panic!("{}", err));
+            }
+            "--standings" => show_standings = true,
+            "--sort" => {
+                sort = args
+                    .next()
+                    .expect(&usage)
+                    .parse()
+                    .unwrap_or_else(|err| panic!("{}", err))
+            }
+            "--desc" => descending = true,
+            "--what-if" => show_what_if = true,
+            _ => panic!("{}", usage),
+        }
+    }
+    if show_standings {
+        let mut standings = standings(input, &ruleset);
+        sort_standings(&mut standings, &ruleset, sort, descending);
+        print!("{}", format_standings(&standings));
+    }
+    if show_what_if {
+        let changes = what_if(input, &Ruleset::standard(), &Ruleset::jokers());
+        print!("{}", format_what_if(&changes));
+    }
+    total_winnings(input, &ruleset)
+}
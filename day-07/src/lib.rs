@@ -0,0 +1,594 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+pub mod cli;
+pub mod standings;
+
+// Rules
+
+/// A hand category, such as "Full house". A hand belongs to it when its
+/// largest groups of equal cards are at least as big as `pattern`, e.g.
+/// `[3, 2]` for a full house or `[]` for a high card.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Category {
+    pub name: String,
+    pub pattern: Vec<u8>,
+}
+
+impl Category {
+    pub fn new(name: &str, pattern: &[u8]) -> Self {
+        Self {
+            name: name.to_string(),
+            pattern: pattern.to_vec(),
+        }
+    }
+    /// `groups` has to be sorted from the largest group down.
+    fn matches(&self, groups: &[u8]) -> bool {
+        self.pattern
+            .iter()
+            .enumerate()
+            .all(|(i, &size)| groups.get(i).is_some_and(|&group| group >= size))
+    }
+}
+
+/// How hands of the same category are ordered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TieBreak {
+    /// Compare the cards one by one in the order they were dealt.
+    Positional,
+    /// Compare the cards from the strongest down, like in poker.
+    Sorted,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ruleset {
+    /// All cards from the weakest to the strongest.
+    pub cards: Vec<char>,
+    /// Cards that count as whatever makes the hand the strongest.
+    pub wildcards: Vec<char>,
+    /// From the weakest to the strongest.
+    pub categories: Vec<Category>,
+    pub tie_break: TieBreak,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    UnknownKey(String),
+    InvalidLine(String),
+    UnknownTieBreak(String),
+    UnknownWildcard(char),
+    DuplicateCard(char),
+    /// The weakest category has to match every hand, so it needs an empty
+    /// pattern.
+    NoCatchAllCategory,
+    Missing(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => write!(f, "Unknown key: {}", key),
+            ConfigError::InvalidLine(line) => write!(f, "Invalid line: {}", line),
+            ConfigError::UnknownTieBreak(rule) => write!(f, "Unknown tie break: {}", rule),
+            ConfigError::UnknownWildcard(card) => {
+                write!(f, "Wildcard {} is not one of the cards", card)
+            }
+            ConfigError::DuplicateCard(card) => write!(f, "Card {} is listed twice", card),
+            ConfigError::NoCatchAllCategory => {
+                write!(f, "The weakest category needs an empty pattern")
+            }
+            ConfigError::Missing(key) => write!(f, "Missing {}", key),
+        }
+    }
+}
+
+impl Ruleset {
+    fn standard_categories() -> Vec<Category> {
+        vec![
+            Category::new("High card", &[]),
+            Category::new("One pair", &[2]),
+            Category::new("Two pair", &[2, 2]),
+            Category::new("Three of a kind", &[3]),
+            Category::new("Full house", &[3, 2]),
+            Category::new("Four of a kind", &[4]),
+            Category::new("Five of a kind", &[5]),
+        ]
+    }
+
+    /// The rules of part 1.
+    pub fn standard() -> Self {
+        Self {
+            cards: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            categories: Self::standard_categories(),
+            tie_break: TieBreak::Positional,
+        }
+    }
+
+    /// The rules of part 2, `J` is the weakest card but a joker.
+    pub fn jokers() -> Self {
+        Self {
+            cards: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            categories: Self::standard_categories(),
+            tie_break: TieBreak::Positional,
+        }
+    }
+
+    /// Reads a ruleset from lines of `key = value`, `#` starts a comment:
+    ///
+    /// ```text
+    /// cards = 23456789TJQKA
+    /// wildcards = J
+    /// category = High card:
+    /// category = One pair: 2
+    /// category = Two pair: 2 2
+    /// tie_break = positional
+    /// ```
+    ///
+    /// Categories are listed from the weakest to the strongest and default to
+    /// the usual ones, the other keys except `cards` are optional as well. The
+    /// weakest category needs an empty pattern so every hand has one.
+    pub fn from_config(input: &str) -> Result<Self, ConfigError> {
+        let mut cards = None;
+        let mut wildcards = Vec::new();
+        let mut categories = Vec::new();
+        let mut tie_break = TieBreak::Positional;
+        for line in input.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || ConfigError::InvalidLine(line.to_string());
+            let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+            let value = value.trim();
+            match key.trim() {
+                "cards" => cards = Some(value.chars().filter(|c| !c.is_whitespace()).collect()),
+                "wildcards" => wildcards = value.chars().filter(|c| !c.is_whitespace()).collect(),
+                "category" => {
+                    let (name, pattern) = value.split_once(':').ok_or_else(invalid)?;
+                    let pattern = pattern
+                        .split_whitespace()
+                        .map(|size| size.parse().map_err(|_| invalid()))
+                        .collect::<Result<Vec<u8>, _>>()?;
+                    categories.push(Category::new(name.trim(), &pattern));
+                }
+                "tie_break" => {
+                    tie_break = match value {
+                        "positional" => TieBreak::Positional,
+                        "sorted" => TieBreak::Sorted,
+                        _ => return Err(ConfigError::UnknownTieBreak(value.to_string())),
+                    }
+                }
+                other => return Err(ConfigError::UnknownKey(other.to_string())),
+            }
+        }
+        let cards: Vec<char> = cards.ok_or(ConfigError::Missing("cards"))?;
+        if let Some((_, &card)) = cards
+            .iter()
+            .enumerate()
+            .find(|&(i, card)| cards[..i].contains(card))
+        {
+            return Err(ConfigError::DuplicateCard(card));
+        }
+        if let Some(&card) = wildcards.iter().find(|card| !cards.contains(card)) {
+            return Err(ConfigError::UnknownWildcard(card));
+        }
+        if categories.is_empty() {
+            categories = Self::standard_categories();
+        } else if !categories[0].pattern.is_empty() {
+            return Err(ConfigError::NoCatchAllCategory);
+        }
+        Ok(Self {
+            cards,
+            wildcards,
+            categories,
+            tie_break,
+        })
+    }
+
+    pub fn card_value(&self, card: char) -> u8 {
+        self.cards
+            .iter()
+            .position(|&c| c == card)
+            .unwrap_or_else(|| panic!("Unknown card: {}", card)) as u8
+    }
+
+    pub fn is_wildcard(&self, card: char) -> bool {
+        self.wildcards.contains(&card)
+    }
+
+    /// Index of the strongest category the cards belong to. Wildcards count as
+    /// whatever makes the hand the strongest, which takes trying every
+    /// substitution: letting them join the largest group is only the best
+    /// choice for some sets of categories.
+    pub fn categorize(&self, cards: &[char]) -> usize {
+        if cards.iter().any(|&card| self.is_wildcard(card)) {
+            self.best_substitution(cards).1
+        } else {
+            self.categorize_natural(cards)
+        }
+    }
+
+    /// Category of the cards when wildcards are just ordinary cards.
+    pub fn categorize_natural(&self, cards: &[char]) -> usize {
+        self.categorize_groups(&Self::groups(cards))
+    }
+
+    /// Sizes of the groups of equal cards, largest first.
+    fn groups(cards: &[char]) -> Vec<u8> {
+        let mut card_counts: HashMap<char, u8> = HashMap::new();
+        for &card in cards {
+            *card_counts.entry(card).or_insert(0) += 1;
+        }
+        let mut groups: Vec<u8> = card_counts.into_values().collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        groups
+    }
+
+    /// The strongest hand the wildcards can be turned into, found by trying
+    /// every combination of ordinary cards for them. On a tie the wildcards
+    /// become the strongest cards.
+    pub fn best_substitution(&self, cards: &[char]) -> (Vec<char>, usize) {
+        let wildcard_count = cards.iter().filter(|&&card| self.is_wildcard(card)).count();
+        let mut replacements: Vec<char> = self
+            .cards
+            .iter()
+            .rev()
+            .copied()
+            .filter(|&card| !self.is_wildcard(card))
+            .collect();
+        if replacements.is_empty() {
+            // Nothing to turn the wildcards into
+            replacements = self.cards.iter().rev().copied().collect();
+        }
+
+        let mut best: Option<(Vec<char>, usize)> = None;
+        for substitution in multisets(&replacements, wildcard_count) {
+            let mut substitution = substitution.into_iter();
+            let substituted: Vec<char> = cards
+                .iter()
+                .map(|&card| match self.is_wildcard(card) {
+                    true => substitution.next().expect("One card per wildcard"),
+                    false => card,
+                })
+                .collect();
+            let category = self.categorize_natural(&substituted);
+            if best.as_ref().is_none_or(|(_, best)| category > *best) {
+                best = Some((substituted, category));
+            }
+        }
+        best.expect("There is at least the empty substitution")
+    }
+
+    /// How wildcards upgrade a hand, keyed by the hand's category with the
+    /// wildcards as ordinary cards and the number of wildcards. Generated by
+    /// resolving every multiset of `hand_size` cards, an `Err` holds a key
+    /// whose hands end up in different categories.
+    pub fn upgrade_table(
+        &self,
+        hand_size: usize,
+    ) -> Result<BTreeMap<(usize, usize), usize>, (usize, usize)> {
+        let mut table = BTreeMap::new();
+        for cards in multisets(&self.cards, hand_size) {
+            let wildcard_count = cards.iter().filter(|&&card| self.is_wildcard(card)).count();
+            let key = (self.categorize_natural(&cards), wildcard_count);
+            let (_, category) = self.best_substitution(&cards);
+            if *table.entry(key).or_insert(category) != category {
+                return Err(key);
+            }
+        }
+        Ok(table)
+    }
+
+    fn categorize_groups(&self, groups: &[u8]) -> usize {
+        self.categories
+            .iter()
+            .rposition(|category| category.matches(groups))
+            .expect("There should be a category for every hand")
+    }
+
+    /// Card values in the order they are compared on a tie.
+    fn tie_break_values(&self, cards: &[char]) -> Vec<u8> {
+        let mut values: Vec<u8> = cards.iter().map(|&card| self.card_value(card)).collect();
+        if self.tie_break == TieBreak::Sorted {
+            values.sort_unstable_by(|a, b| b.cmp(a));
+        }
+        values
+    }
+}
+
+/// Every way to pick `count` cards with repetition, in the order of `cards`.
+fn multisets(cards: &[char], count: usize) -> Vec<Vec<char>> {
+    if count == 0 {
+        return vec![Vec::new()];
+    }
+    let mut result = Vec::new();
+    for (i, &card) in cards.iter().enumerate() {
+        for mut rest in multisets(&cards[i..], count - 1) {
+            rest.insert(0, card);
+            result.push(rest);
+        }
+    }
+    result
+}
+
+// Hands
+
+pub struct Hand {
+    pub cards: Vec<char>,
+    pub points: u32,
+    pub category: usize,
+    /// Position in the input, decides between otherwise equal hands.
+    pub index: usize,
+    tie_break_values: Vec<u8>,
+}
+
+impl Hand {
+    pub fn parse(line: &str, ruleset: &Ruleset) -> Self {
+        let (cards, points) = Self::parse_line(line);
+        let category = ruleset.categorize(&cards);
+        Self::new(cards, points, category, ruleset)
+    }
+
+    fn parse_line(line: &str) -> (Vec<char>, u32) {
+        let mut splitted = line.split_whitespace();
+        let cards: Vec<char> = splitted
+            .next()
+            .expect("Should be in right format.")
+            .chars()
+            .collect();
+        let points: u32 = splitted
+            .next()
+            .expect("Should be in right format.")
+            .parse()
+            .expect("Should be a number");
+        (cards, points)
+    }
+
+    fn new(cards: Vec<char>, points: u32, category: usize, ruleset: &Ruleset) -> Self {
+        Self {
+            category,
+            tie_break_values: ruleset.tie_break_values(&cards),
+            cards,
+            points,
+            index: 0,
+        }
+    }
+
+    /// The cards with every wildcard replaced by what it counts as. This tries
+    /// every substitution, so it is only worked out when asked for.
+    pub fn best_substitution(&self, ruleset: &Ruleset) -> Vec<char> {
+        ruleset.best_substitution(&self.cards).0
+    }
+}
+
+impl PartialEq for Hand {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Hand {}
+
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Hand {
+    /// Weakest first. Of two hands with the same cards the one that comes
+    /// first in the input is the weaker one.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.category
+            .cmp(&other.category)
+            .then_with(|| self.tie_break_values.cmp(&other.tie_break_values))
+            .then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+pub fn parse_hands(input: &str, ruleset: &Ruleset) -> Vec<Hand> {
+    // Categorizing a hand with wildcards tries every substitution, so it is
+    // done once per set of cards
+    let mut categories: HashMap<Vec<char>, usize> = HashMap::new();
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let (cards, points) = Hand::parse_line(line);
+            let mut sorted = cards.clone();
+            sorted.sort_unstable();
+            let category = *categories
+                .entry(sorted)
+                .or_insert_with_key(|sorted| ruleset.categorize(sorted));
+            Hand {
+                index,
+                ..Hand::new(cards, points, category, ruleset)
+            }
+        })
+        .collect()
+}
+
+/// Sum of every hand's points times its rank, the weakest hand has rank 1.
+pub fn total_winnings(input: &str, ruleset: &Ruleset) -> u64 {
+    let mut hands = parse_hands(input, ruleset);
+    hands.sort_unstable();
+    hands
+        .iter()
+        .enumerate()
+        .map(|(idx, hand)| {
+            let rank = idx as u64 + 1;
+            rank.checked_mul(hand.points as u64)
+                .expect("Winnings too large for u64")
+        })
+        .try_fold(0u64, |sum, winnings| sum.checked_add(winnings))
+        .expect("Winnings too large for u64")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483
+";
+
+    #[test]
+    fn custom_ruleset() {
+        // Part 2 written down as a config
+        let config = "
+# Jokers are wild but weak
+cards = J 2 3 4 5 6 7 8 9 T Q K A
+wildcards = J
+tie_break = positional
+";
+        let ruleset = Ruleset::from_config(config).unwrap();
+        assert_eq!(total_winnings(INPUT, &ruleset), 5905);
+
+        // Poker like ordering with any queen or king being wild
+        let config = "cards = 23456789TJQKA
+wildcards = QK
+category = Nothing:
+category = Pair: 2
+category = Triple: 3
+category = Quad: 4
+tie_break = sorted
+";
+        let ruleset = Ruleset::from_config(config).unwrap();
+        let names: Vec<&str> = parse_hands(INPUT, &ruleset)
+            .iter()
+            .map(|hand| ruleset.categories[hand.category].name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Triple", "Triple", "Quad", "Triple", "Quad"]);
+    }
+
+    #[test]
+    fn joker_substitution() {
+        let ruleset = Ruleset::jokers();
+        let hands = parse_hands(INPUT, &ruleset);
+        let substituted: Vec<String> = hands
+            .iter()
+            .map(|hand| hand.best_substitution(&ruleset).iter().collect())
+            .collect();
+        assert_eq!(
+            substituted,
+            vec!["32T3K", "T5555", "KK677", "KTTTT", "QQQQA"]
+        );
+
+        let hand = Hand::parse("JJJJJ 1", &ruleset);
+        assert_eq!(hand.best_substitution(&ruleset), vec!['A'; 5]);
+        assert_eq!(ruleset.categories[hand.category].name, "Five of a kind");
+    }
+
+    #[test]
+    fn joker_upgrade_table() {
+        let ruleset = Ruleset::jokers();
+        let table = ruleset.upgrade_table(5).unwrap();
+        let category = |name: &str| {
+            ruleset
+                .categories
+                .iter()
+                .position(|category| category.name == name)
+                .unwrap()
+        };
+        // The hand written table part 2 used to have, jokers counted as `J`
+        let expected = [
+            ("High card", 0, "High card"),
+            ("High card", 1, "One pair"),
+            ("One pair", 0, "One pair"),
+            ("One pair", 1, "Three of a kind"),
+            ("One pair", 2, "Three of a kind"),
+            ("Two pair", 0, "Two pair"),
+            ("Two pair", 1, "Full house"),
+            ("Two pair", 2, "Four of a kind"),
+            ("Three of a kind", 0, "Three of a kind"),
+            ("Three of a kind", 1, "Four of a kind"),
+            ("Three of a kind", 3, "Four of a kind"),
+            ("Full house", 0, "Full house"),
+            ("Full house", 2, "Five of a kind"),
+            ("Full house", 3, "Five of a kind"),
+            ("Four of a kind", 0, "Four of a kind"),
+            ("Four of a kind", 1, "Five of a kind"),
+            ("Four of a kind", 4, "Five of a kind"),
+            ("Five of a kind", 0, "Five of a kind"),
+            ("Five of a kind", 5, "Five of a kind"),
+        ];
+        let expected: BTreeMap<(usize, usize), usize> = expected
+            .into_iter()
+            .map(|(from, jokers, to)| ((category(from), jokers), category(to)))
+            .collect();
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn wildcards_split_groups() {
+        // Two pair beats a triple, so a joker is better off starting a second
+        // pair than joining the first one
+        let config = "cards = 23456789TJQKA
+wildcards = J
+category = High:
+category = Triple: 3
+category = TwoPair: 2 2
+";
+        let ruleset = Ruleset::from_config(config).unwrap();
+        let hands = parse_hands("AAKQJ 1\n2223K 2\n", &ruleset);
+        assert_eq!(ruleset.categories[hands[0].category].name, "TwoPair");
+        assert_eq!(
+            hands[0].best_substitution(&ruleset),
+            vec!['A', 'A', 'K', 'Q', 'K']
+        );
+        assert!(hands[0] > hands[1]);
+        assert_eq!(total_winnings("AAKQJ 1\n2223K 2\n", &ruleset), 4);
+    }
+
+    #[test]
+    fn duplicate_hands() {
+        for ruleset in [Ruleset::standard(), Ruleset::jokers()] {
+            assert_eq!(total_winnings("AAAAA 1\nAAAAA 2\n", &ruleset), 5);
+            assert_eq!(total_winnings("AAAAA 2\nAAAAA 1\n", &ruleset), 4);
+
+            let hands = parse_hands("KTJJT 3\n32T3K 1\nKTJJT 2\n", &ruleset);
+            assert!(hands[0] < hands[2]);
+            assert!(hands[0] != hands[2]);
+        }
+
+        // Repeated hands with ranks and bids that overflow u32
+        let input = "T55J5 1000000\n".repeat(10_000);
+        let expected = 1_000_000 * 10_000 * 10_001 / 2;
+        assert_eq!(total_winnings(&input, &Ruleset::jokers()), expected);
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert_eq!(
+            Ruleset::from_config("wildcards = J").err(),
+            Some(ConfigError::Missing("cards"))
+        );
+        assert_eq!(
+            Ruleset::from_config("cards = 123\nwildcards = J").err(),
+            Some(ConfigError::UnknownWildcard('J'))
+        );
+        assert_eq!(
+            Ruleset::from_config("cards = 123\ntie_break = random").err(),
+            Some(ConfigError::UnknownTieBreak("random".to_string()))
+        );
+        assert_eq!(
+            Ruleset::from_config("cards = 123\nrounds = 3").err(),
+            Some(ConfigError::UnknownKey("rounds".to_string()))
+        );
+        assert_eq!(
+            Ruleset::from_config("cards = 1231").err(),
+            Some(ConfigError::DuplicateCard('1'))
+        );
+        assert_eq!(
+            Ruleset::from_config("cards = 23456789TJQKA\ncategory = Pair: 2").err(),
+            Some(ConfigError::NoCatchAllCategory)
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::{parse_hands, Ruleset};
+
+pub struct Standing {
+    pub rank: usize,
+    pub cards: String,
+    pub category: usize,
+    pub category_name: String,
+    /// The cards with the wildcards replaced, only if there are any.
+    pub substituted: Option<String>,
+    pub bid: u32,
+    pub winnings: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    Rank,
+    Hand,
+    Type,
+    Substituted,
+    Bid,
+    Winnings,
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rank" => Ok(Column::Rank),
+            "hand" => Ok(Column::Hand),
+            "type" => Ok(Column::Type),
+            "substituted" => Ok(Column::Substituted),
+            "bid" => Ok(Column::Bid),
+            "winnings" => Ok(Column::Winnings),
+            _ => Err(format!(
+                "Unknown column: {}, use rank, hand, type, substituted, bid or winnings",
+                s
+            )),
+        }
+    }
+}
+
+/// Every hand with its rank and winnings, from the weakest hand up.
+pub fn standings(input: &str, ruleset: &Ruleset) -> Vec<Standing> {
+    let mut hands = parse_hands(input, ruleset);
+    hands.sort_unstable();
+    hands
+        .iter()
+        .enumerate()
+        .map(|(idx, hand)| {
+            let rank = idx + 1;
+            let substituted = hand.best_substitution(ruleset);
+            Standing {
+                rank,
+                cards: hand.cards.iter().collect(),
+                category: hand.category,
+                category_name: ruleset.categories[hand.category].name.clone(),
+                substituted: (substituted != hand.cards).then(|| substituted.iter().collect()),
+                bid: hand.points,
+                winnings: rank as u64 * hand.points as u64,
+            }
+        })
+        .collect()
+}
+
+/// Sorts by `column`, the rank decides between equal values in either
+/// direction. Types are sorted by strength, not by name, and hands by the
+/// values of their cards in the ruleset.
+pub fn sort_standings(
+    standings: &mut [Standing],
+    ruleset: &Ruleset,
+    column: Column,
+    descending: bool,
+) {
+    let by_strength = |a: &str, b: &str| {
+        let value = |card| ruleset.card_value(card);
+        a.chars().map(value).cmp(b.chars().map(value))
+    };
+    standings.sort_by(|a, b| {
+        let order = match column {
+            Column::Rank => a.rank.cmp(&b.rank),
+            Column::Hand => by_strength(&a.cards, &b.cards),
+            Column::Type => a.category.cmp(&b.category),
+            Column::Substituted => by_strength(
+                a.substituted.as_ref().unwrap_or(&a.cards),
+                b.substituted.as_ref().unwrap_or(&b.cards),
+            ),
+            Column::Bid => a.bid.cmp(&b.bid),
+            Column::Winnings => a.winnings.cmp(&b.winnings),
+        };
+        let order = if descending { order.reverse() } else { order };
+        order.then_with(|| a.rank.cmp(&b.rank))
+    });
+}
+
+pub fn format_standings(standings: &[Standing]) -> String {
+    let rows: Vec<[String; 6]> = standings
+        .iter()
+        .map(|standing| {
+            [
+                standing.rank.to_string(),
+                standing.cards.clone(),
+                standing.category_name.clone(),
+                standing.substituted.clone().unwrap_or_default(),
+                standing.bid.to_string(),
+                standing.winnings.to_string(),
+            ]
+        })
+        .collect();
+    format_table(
+        ["Rank", "Hand", "Type", "Substituted", "Bid", "Winnings"],
+        &rows,
+    )
+}
+
+pub struct RankChange {
+    pub cards: String,
+    pub bid: u32,
+    pub before: usize,
+    pub after: usize,
+}
+
+/// How the rank of every hand changes from the `before` to the `after` rules,
+/// in input order.
+pub fn what_if(input: &str, before: &Ruleset, after: &Ruleset) -> Vec<RankChange> {
+    let rank_by_index = |ruleset: &Ruleset| -> HashMap<usize, usize> {
+        let mut hands = parse_hands(input, ruleset);
+        hands.sort_unstable();
+        hands
+            .iter()
+            .enumerate()
+            .map(|(idx, hand)| (hand.index, idx + 1))
+            .collect()
+    };
+    let (ranks_before, ranks_after) = (rank_by_index(before), rank_by_index(after));
+    parse_hands(input, before)
+        .into_iter()
+        .map(|hand| RankChange {
+            before: ranks_before[&hand.index],
+            after: ranks_after[&hand.index],
+            cards: hand.cards.iter().collect(),
+            bid: hand.points,
+        })
+        .collect()
+}
+
+/// Biggest gains first.
+pub fn format_what_if(changes: &[RankChange]) -> String {
+    let mut changes: Vec<&RankChange> = changes.iter().collect();
+    changes.sort_by_key(|change| {
+        (
+            Reverse(change.after as isize - change.before as isize),
+            change.after,
+        )
+    });
+    let rows: Vec<[String; 5]> = changes
+        .iter()
+        .map(|change| {
+            [
+                change.cards.clone(),
+                change.bid.to_string(),
+                change.before.to_string(),
+                change.after.to_string(),
+                format!("{:+}", change.after as isize - change.before as isize),
+            ]
+        })
+        .collect();
+    format_table(["Hand", "Bid", "Before", "After", "Change"], &rows)
+}
+
+fn format_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) -> String {
+    let mut widths = header.map(|title| title.len());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let format_row = |cells: Vec<&str>| -> String {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        cells.join(" | ").trim_end().to_string() + "\n"
+    };
+    let mut table = format_row(header.to_vec());
+    let separator: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    table.push_str(&separator.join("-+-"));
+    table.push('\n');
+    for row in rows {
+        table.push_str(&format_row(row.iter().map(String::as_str).collect()));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483
+";
+
+    #[test]
+    fn standings_table() {
+        let mut standings = standings(INPUT, &Ruleset::jokers());
+        let total: u64 = standings.iter().map(|standing| standing.winnings).sum();
+        assert_eq!(total, 5905);
+
+        sort_standings(&mut standings, &Ruleset::jokers(), Column::Bid, true);
+        assert_eq!(
+            format_standings(&standings),
+            "\
+Rank | Hand  | Type           | Substituted | Bid | Winnings
+-----+-------+----------------+-------------+-----+---------
+1    | 32T3K | One pair       |             | 765 | 765
+3    | T55J5 | Four of a kind | T5555       | 684 | 2052
+4    | QQQJA | Four of a kind | QQQQA       | 483 | 1932
+5    | KTJJT | Four of a kind | KTTTT       | 220 | 1100
+2    | KK677 | Two pair       |             | 28  | 56
+"
+        );
+    }
+
+    #[test]
+    fn sort_by_strength() {
+        let ruleset = Ruleset::jokers();
+        let mut standings = standings("AJ234 1\n2345J 1\nKQ234 2\n", &ruleset);
+        let hands = |standings: &[Standing]| -> Vec<String> {
+            standings
+                .iter()
+                .map(|standing| standing.cards.clone())
+                .collect()
+        };
+
+        // `J` is the weakest card here, although it comes after `A` in order
+        sort_standings(&mut standings, &ruleset, Column::Hand, false);
+        assert_eq!(hands(&standings), vec!["2345J", "KQ234", "AJ234"]);
+
+        // Equal bids keep ranking from the weakest up when sorted down
+        sort_standings(&mut standings, &ruleset, Column::Bid, true);
+        assert_eq!(hands(&standings), vec!["KQ234", "2345J", "AJ234"]);
+    }
+
+    #[test]
+    fn what_if_table() {
+        let changes = what_if(INPUT, &Ruleset::standard(), &Ruleset::jokers());
+        let ranks: Vec<(usize, usize)> = changes
+            .iter()
+            .map(|change| (change.before, change.after))
+            .collect();
+        assert_eq!(ranks, vec![(1, 1), (4, 3), (3, 2), (2, 5), (5, 4)]);
+        assert!(format_what_if(&changes).contains("\nKTJJT | 220 | 2      | 5     | +3\n"));
+    }
+}
@@ -1,129 +1,159 @@
-use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+use day_08::dot::DotExport;
+use day_08::trace::{Trace, TraceFormat};
+use day_08::{
+    check_reachable, cycle_nodes, parse_network, Direction, Network, Roundabout, WalkError,
+};
+
+const USAGE: &str = "Usage: part1 [--max-steps <n>] [--dot <file|-> [--dot-cycles]] \
+     [--trace <full|summary> [--trace-file <file>]]";
 
 fn main() {
     println!("Part 1");
     let input = include_str!("./input1.txt");
-    let result = solve(input);
-    println!("Result = {}", result);
-}
-
-enum Direction {
-    Left,
-    Right,
-}
-
-impl Direction {
-    fn parse(input: char) -> Direction {
-        match input {
-            'R' => Direction::Right,
-            'L' => Direction::Left,
-            _ => panic!("Invalid direction"),
+    let mut max_steps = None;
+    let (mut dot, mut dot_cycles) = (None, false);
+    let (mut trace_format, mut trace_file) = (None, None);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-steps" => {
+                max_steps = Some(args.next().and_then(|n| n.parse().ok()).expect(USAGE))
+            }
+            "--dot" => dot = Some(args.next().expect(USAGE)),
+            "--dot-cycles" => dot_cycles = true,
+            "--trace" => {
+                trace_format = Some(
+                    args.next()
+                        .expect(USAGE)
+                        .parse()
+                        .unwrap_or_else(|err| panic!("{}", err)),
+                )
+            }
+            "--trace-file" => trace_file = Some(args.next().expect(USAGE)),
+            _ => panic!("{}", USAGE),
         }
     }
-}
-
-struct Roundabout<T> {
-    data: Vec<T>,
-    current_index: usize,
-}
-
-impl<T> Roundabout<T> {
-    fn new(data: Vec<T>) -> Self {
-        Self {
-            data,
-            current_index: 0,
+    if let Some(path) = dot {
+        let dot = export_dot(input, dot_cycles);
+        match path.as_str() {
+            "-" => print!("{}", dot),
+            path => fs::write(path, dot).expect("Could not write the graph"),
         }
     }
-    fn add(&mut self, item: T) {
-        self.data.push(item);
-    }
-
-    fn next(&mut self) -> Option<&T> {
-        let result = self.data.get(self.current_index);
-        self.current_index += 1;
-        if self.current_index == self.data.len() {
-            self.current_index = 0;
+    if let Some(format) = trace_format {
+        let trace = trace(input, max_steps, format);
+        match trace_file {
+            None => print!("{}", trace),
+            Some(path) => fs::write(path, trace).expect("Could not write the trace"),
         }
-        result
     }
+    let result = match max_steps {
+        None => solve(input),
+        Some(_) => solve_with(input, max_steps).unwrap_or_else(|err| panic!("{}", err)),
+    };
+    println!("Result = {}", result);
 }
 
 struct Game {
-    nodes: HashMap<String, (String, String)>,
-    current_node: String,
-    final_node: String,
+    network: Network,
+    current_node: usize,
+    final_node: usize,
     directions: Roundabout<Direction>,
     steps: usize,
+    /// Gives up after this many steps instead of walking on.
+    max_steps: Option<usize>,
+    trace: Option<Trace>,
 }
 
 impl Game {
-    fn run(&mut self) {
-        while !self.current_node.eq(&self.final_node) {
+    fn run(&mut self) -> Result<(), WalkError> {
+        let final_node = self.final_node;
+        check_reachable(&self.network, &[self.current_node], |node| {
+            node == final_node
+        })?;
+        while self.current_node != self.final_node {
+            self.record();
+            if self
+                .max_steps
+                .is_some_and(|max_steps| self.steps >= max_steps)
+            {
+                return Err(WalkError::OutOfSteps {
+                    steps: self.steps as u64,
+                    node: self.network.name(self.current_node).to_string(),
+                    instruction: self.directions.current_index,
+                });
+            }
             self.next_node();
         }
+        self.record();
+        Ok(())
     }
-    fn next_node(&mut self) {
-        let (left, right) = self.nodes.get(&self.current_node).unwrap();
-        let direction = self.directions.next().unwrap();
-        match direction {
-            Direction::Left => self.current_node = left.clone(),
-            Direction::Right => self.current_node = right.clone(),
+    fn record(&mut self) {
+        if let Some(trace) = &mut self.trace {
+            trace.record(self.current_node, self.directions.current_index);
         }
+    }
+    fn next_node(&mut self) {
+        let &direction = self.directions.next().unwrap();
+        self.current_node = self.network.step(self.current_node, direction);
         self.steps += 1;
     }
 }
 
 fn solve(input: &str) -> String {
+    solve_with(input, None).unwrap_or_else(|err| panic!("{}", err))
+}
+
+fn solve_with(input: &str, max_steps: Option<usize>) -> Result<String, WalkError> {
     let mut game = parse_input(input);
-    game.run();
-    game.steps.to_string()
+    game.max_steps = max_steps;
+    game.run()?;
+    Ok(game.steps.to_string())
 }
 
-fn parse_input(input: &str) -> Game {
-    let mut nodes = HashMap::new();
-    let mut starts = Vec::new();
-    let mut directions = Roundabout::new(Vec::new());
-    let steps = 0;
-
-    let mut lines = input.lines();
-    let first_line = lines.next().unwrap();
-    for c in first_line.chars() {
-        directions.add(Direction::parse(c));
-    }
-    lines.next(); // skip blank line
-    for line in lines {
-        let parts: Vec<&str> = line.split('=').collect();
-        let start = parts[0].trim().to_string();
-        let mut node = parts[1].split(',');
-        let left = node
-            .next()
-            .expect("Should be in right format")
-            .replace('(', " ")
-            .trim()
-            .to_string();
-        let right = node
-            .next()
-            .expect("Should be in right format")
-            .replace(')', " ")
-            .trim()
-            .to_string();
-        starts.push(start.clone());
-        nodes.insert(start, (left, right));
+/// The walk up to the goal or up to where it gives up.
+fn trace(input: &str, max_steps: Option<usize>, format: TraceFormat) -> String {
+    let mut game = parse_input(input);
+    game.max_steps = max_steps;
+    game.trace = Some(Trace::default());
+    if let Err(err) = game.run() {
+        eprintln!("{}", err);
     }
-    let current_node = String::from("AAA");
-    let final_node = String::from("ZZZ");
+    game.trace.unwrap().format(&game.network, format)
+}
+
+fn parse_input(input: &str) -> Game {
+    let network = parse_network(input).unwrap_or_else(|err| panic!("{}", err));
+    let current_node = network.id("AAA").expect("AAA should be defined");
+    let final_node = network.id("ZZZ").expect("ZZZ should be defined");
     Game {
-        nodes,
+        directions: Roundabout::new(network.directions.clone()),
+        network,
         current_node,
         final_node,
-        directions,
-        steps,
+        steps: 0,
+        max_steps: None,
+        trace: None,
+    }
+}
+
+fn export_dot(input: &str, with_cycles: bool) -> String {
+    let game = parse_input(input);
+    let mut export = DotExport::new(&game.network);
+    if with_cycles {
+        export = export.with_cycles(vec![cycle_nodes(&game.network, game.current_node)]);
     }
+    export.render()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::solve;
+    use crate::{solve, solve_with, trace};
+    use day_08::trace::TraceFormat;
+    use day_08::WalkError;
 
     #[test]
     fn example_input() {
@@ -152,5 +182,67 @@ ZZZ = (ZZZ, ZZZ)";
         let output = solve(input);
         assert_eq!(output, "6".to_string());
     }
-}
 
+    #[test]
+    fn unreachable_goal() {
+        let input = "LR
+
+AAA = (BBB, BBB)
+BBB = (AAA, CCC)
+CCC = (AAA, AAA)
+ZZZ = (ZZZ, ZZZ)
+";
+        assert_eq!(
+            solve_with(input, None),
+            Err(WalkError::Unreachable(vec!["AAA".to_string()]))
+        );
+    }
+
+    #[test]
+    fn step_budget() {
+        let input = "LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)";
+        assert_eq!(
+            solve_with(input, Some(4)),
+            Err(WalkError::OutOfSteps {
+                steps: 4,
+                node: "AAA".to_string(),
+                instruction: 1,
+            })
+        );
+        assert_eq!(solve_with(input, Some(6)), Ok("6".to_string()));
+    }
+
+    #[test]
+    fn trace_wraps_instructions() {
+        let input = "LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)";
+        assert_eq!(
+            trace(input, None, TraceFormat::Full),
+            "\
+step\tinstruction\tnode
+0\t0\tAAA
+1\t1\tBBB
+2\t2\tAAA
+3\t0\tBBB
+4\t1\tAAA
+5\t2\tBBB
+6\t0\tZZZ
+"
+        );
+        assert_eq!(
+            trace(input, None, TraceFormat::Summary),
+            "\
+steps 0-2: AAA .. AAA (instructions 0-2)
+steps 3-5: BBB .. BBB (instructions 0-2)
+steps 6-6: ZZZ .. ZZZ (instructions 0-0)
+"
+        );
+    }
+}
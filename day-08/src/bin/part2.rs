@@ -1,142 +1,241 @@
-use num::integer::lcm;
+use num::integer::{ExtendedGcd, Integer};
 use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+use day_08::dot::DotExport;
+use day_08::{
+    check_reachable, cycle_nodes, parse_network, Direction, Network, Roundabout, WalkError,
+};
+
+const USAGE: &str = "Usage: part2 [--max-steps <n>] [--dot <file|-> [--dot-cycles]]";
 
 fn main() {
     println!("Part 2");
     let input = include_str!("./input2.txt");
-    let result = solve(input);
-    println!("Result = {}", result);
-}
-
-#[derive(Debug, Copy, Clone)]
-enum Direction {
-    Left,
-    Right,
-}
-
-impl Direction {
-    fn parse(input: char) -> Direction {
-        match input {
-            'R' => Direction::Right,
-            'L' => Direction::Left,
-            _ => panic!("Invalid direction"),
-        }
-    }
-}
-
-struct Roundabout<T> {
-    data: Vec<T>,
-    current_index: usize,
-}
-
-impl<T> Roundabout<T> {
-    fn new(data: Vec<T>) -> Self {
-        Self {
-            data,
-            current_index: 0,
+    let mut max_steps = None;
+    let (mut dot, mut dot_cycles) = (None, false);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-steps" => {
+                max_steps = Some(args.next().and_then(|n| n.parse().ok()).expect(USAGE))
+            }
+            "--dot" => dot = Some(args.next().expect(USAGE)),
+            "--dot-cycles" => dot_cycles = true,
+            _ => panic!("{}", USAGE),
         }
     }
-    fn add(&mut self, item: T) {
-        self.data.push(item);
-    }
-
-    fn next(&mut self) -> Option<&T> {
-        let result = self.data.get(self.current_index);
-        self.current_index += 1;
-        if self.current_index == self.data.len() {
-            self.current_index = 0;
+    if let Some(path) = dot {
+        let dot = export_dot(input, dot_cycles);
+        match path.as_str() {
+            "-" => print!("{}", dot),
+            path => fs::write(path, dot).expect("Could not write the graph"),
         }
-        result
     }
+    let result = match max_steps {
+        None => solve(input),
+        Some(_) => solve_with(input, max_steps).unwrap_or_else(|err| panic!("{}", err)),
+    };
+    println!("Result = {}", result);
 }
 
-#[derive(Default)]
-struct GameData {
-    raw_strings: Vec<String>,
+struct Game {
+    network: Network,
+    current_nodes: Vec<usize>,
+    /// Whether the node with this id is a `..Z` node.
+    goals: Vec<bool>,
+    directions: Roundabout<Direction>,
+    /// Gives up on a walk after this many steps instead of searching on.
+    max_steps: Option<u64>,
 }
 
-struct Game<'a> {
-    nodes: HashMap<&'a str, (&'a str, &'a str)>,
-    current_nodes: Vec<&'a str>,
-    directions: Roundabout<Direction>,
+/// The walk of one start node through the (node, instruction index) states.
+/// Every step at which it stands on a `..Z` node is either in `pre_cycle_hits`
+/// or one of the `cycle_hits` plus a multiple of `length`.
+#[derive(Debug, PartialEq)]
+struct GhostCycle {
+    /// Steps taken before the walk enters its cycle.
+    offset: u64,
+    length: u64,
+    pre_cycle_hits: Vec<u64>,
+    /// Steps of the first pass through the cycle, between `offset` and
+    /// `offset + length`.
+    cycle_hits: Vec<u64>,
 }
 
-impl<'a> Game<'a> {
-    fn run(&mut self) -> u64 {
-        let mut iterations: Vec<u64> = Vec::new();
-        for node in &self.current_nodes {
-            iterations.push(self.get_iteration_len(node));
+impl GhostCycle {
+    fn hits_at(&self, step: u64) -> bool {
+        if step < self.offset {
+            return self.pre_cycle_hits.contains(&step);
         }
+        let step_in_cycle = self.offset + (step - self.offset) % self.length;
+        self.cycle_hits.contains(&step_in_cycle)
+    }
+}
 
-        iterations.iter().fold(1u64, |prev, &x| lcm(prev, x))
+impl Game {
+    /// The first step at which every ghost stands on a `..Z` node, if there is one.
+    fn run(&mut self) -> Result<Option<u64>, WalkError> {
+        check_reachable(&self.network, &self.current_nodes, |node| self.goals[node])?;
+        let cycles = self
+            .current_nodes
+            .iter()
+            .map(|&node| self.get_cycle(node))
+            .collect::<Result<Vec<GhostCycle>, WalkError>>()?;
+        Ok(first_common_hit(&cycles))
     }
-    fn get_iteration_len(&self, node: &str) -> u64 {
-        let mut len = 0;
+    fn get_cycle(&self, node: usize) -> Result<GhostCycle, WalkError> {
+        let mut seen: HashMap<(usize, usize), u64> = HashMap::new();
+        let mut hits = Vec::new();
         let mut current_node = node;
         let mut directions = Roundabout::new(self.directions.data.clone());
+        let mut step = 0;
         loop {
-            len += 1;
-            let &(left, right) = self.nodes.get(current_node).unwrap();
-            current_node = match directions.next().unwrap() {
-                Direction::Right => right,
-                Direction::Left => left,
-            };
-            if current_node.ends_with('Z') {
-                break;
+            if let Some(&offset) = seen.get(&(current_node, directions.current_index)) {
+                let (pre_cycle_hits, cycle_hits) = hits.iter().partition(|&&hit| hit < offset);
+                return Ok(GhostCycle {
+                    offset,
+                    length: step - offset,
+                    pre_cycle_hits,
+                    cycle_hits,
+                });
+            }
+            if self.max_steps.is_some_and(|max_steps| step >= max_steps) {
+                return Err(WalkError::OutOfSteps {
+                    steps: step,
+                    node: self.network.name(current_node).to_string(),
+                    instruction: directions.current_index,
+                });
             }
+            seen.insert((current_node, directions.current_index), step);
+            if self.goals[current_node] {
+                hits.push(step);
+            }
+            let &direction = directions.next().unwrap();
+            current_node = self.network.step(current_node, direction);
+            step += 1;
         }
-        len
     }
 }
 
-fn solve(input: &str) -> String {
-    let data = parse_data(input);
-    let mut game = parse_input(&data);
-    game.run().to_string()
-}
+fn first_common_hit(cycles: &[GhostCycle]) -> Option<u64> {
+    let latest = cycles.iter().max_by_key(|cycle| cycle.offset)?;
+    // Before the last ghost enters its cycle, only its pre-cycle hits qualify.
+    let early = latest
+        .pre_cycle_hits
+        .iter()
+        .copied()
+        .filter(|&step| cycles.iter().all(|cycle| cycle.hits_at(step)))
+        .min();
+    if early.is_some() {
+        return early;
+    }
 
-fn parse_data(input: &str) -> GameData {
-    let mut data = GameData::default();
-    for line in input.lines() {
-        data.raw_strings.push(line.trim().to_string());
+    // After that, every ghost is in its cycle, so each one contributes one
+    // congruence per hit and any combination of them may be solvable.
+    let mut residues = vec![0u128];
+    let mut modulus = 1u128;
+    for cycle in cycles {
+        let length = cycle.length as u128;
+        let mut combined = Vec::new();
+        let mut combined_modulus = modulus;
+        for &residue in &residues {
+            for &hit in &cycle.cycle_hits {
+                if let Some((x, m)) = crt((residue, modulus), (hit as u128 % length, length)) {
+                    combined.push(x);
+                    combined_modulus = m;
+                }
+            }
+        }
+        combined.sort_unstable();
+        combined.dedup();
+        if combined.is_empty() {
+            return None;
+        }
+        residues = combined;
+        modulus = combined_modulus;
     }
-    data
-}
 
-fn parse_input(data: &GameData) -> Game {
-    let mut nodes = HashMap::new();
-    let mut directions = Roundabout::new(Vec::new());
-    let mut current_nodes = Vec::new();
+    let start = latest.offset as u128;
+    residues
+        .into_iter()
+        .map(|residue| {
+            if residue >= start {
+                residue
+            } else {
+                residue + (start - residue).div_ceil(modulus) * modulus
+            }
+        })
+        .min()
+        .map(|step| step.try_into().expect("Step should fit into u64"))
+}
 
-    let mut lines = data.raw_strings.iter();
-    let first_line = lines.next().unwrap();
-    for c in first_line.chars() {
-        directions.add(Direction::parse(c));
+/// Solves `x = a1 mod m1` and `x = a2 mod m2` for moduli that don't need to
+/// be coprime, returning `x` and the modulus of the solution.
+fn crt((a1, m1): (u128, u128), (a2, m2): (u128, u128)) -> Option<(u128, u128)> {
+    let (a1, m1, a2, m2) = (a1 as i128, m1 as i128, a2 as i128, m2 as i128);
+    let ExtendedGcd { gcd: g, x: p, .. } = m1.extended_gcd(&m2);
+    if (a2 - a1) % g != 0 {
+        return None;
     }
-    lines.next(); // skip blank line
-                  // NQH = (HPR, DTN)
-    for line in lines {
-        let start = &line[..3];
+    let lcm = m1 / g * m2;
+    let k = ((a2 - a1) / g % (m2 / g)) * (p % (m2 / g)) % (m2 / g);
+    let x = (a1 + m1 * k).rem_euclid(lcm);
+    Some((x as u128, lcm as u128))
+}
 
-        let left = &line[7..10];
-        let right = &line[12..15];
+fn solve(input: &str) -> String {
+    solve_with(input, None).unwrap_or_else(|err| panic!("{}", err))
+}
 
-        if start.ends_with('A') {
-            current_nodes.push(start);
-        }
-        nodes.insert(start, (left, right));
-    }
+fn solve_with(input: &str, max_steps: Option<u64>) -> Result<String, WalkError> {
+    let mut game = parse_input(input);
+    game.max_steps = max_steps;
+    Ok(match game.run()? {
+        Some(steps) => steps.to_string(),
+        None => "never".to_string(),
+    })
+}
+
+fn parse_input(input: &str) -> Game {
+    let network = parse_network(input).unwrap_or_else(|err| panic!("{}", err));
+    let current_nodes = (0..network.names().len())
+        .filter(|&id| network.name(id).ends_with('A'))
+        .collect();
+    let goals = network
+        .names()
+        .iter()
+        .map(|name| name.ends_with('Z'))
+        .collect();
 
     Game {
-        nodes,
+        directions: Roundabout::new(network.directions.clone()),
+        network,
         current_nodes,
-        directions,
+        goals,
+        max_steps: None,
     }
 }
 
+fn export_dot(input: &str, with_cycles: bool) -> String {
+    let game = parse_input(input);
+    let mut export = DotExport::new(&game.network);
+    if with_cycles {
+        let cycles = game
+            .current_nodes
+            .iter()
+            .map(|&start| cycle_nodes(&game.network, start))
+            .collect();
+        export = export.with_cycles(cycles);
+    }
+    export.render()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::solve;
+    use crate::{parse_input, solve, solve_with, GhostCycle};
+    use day_08::WalkError;
 
     #[test]
     fn example_input() {
@@ -154,5 +253,113 @@ XXX = (XXX, XXX)
         let output = solve(input);
         assert_eq!(output, "6".to_string());
     }
-}
 
+    #[test]
+    fn cycles_with_several_hits() {
+        let input = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)
+";
+        let game = parse_input(input);
+        assert_eq!(
+            game.get_cycle(game.network.id("22A").unwrap()),
+            Ok(GhostCycle {
+                offset: 1,
+                length: 6,
+                pre_cycle_hits: vec![],
+                cycle_hits: vec![3, 6],
+            })
+        );
+    }
+
+    #[test]
+    fn misaligned_cycles() {
+        // The first hits are at 2 and 1, but the cycles are 3 and 2 long
+        let input = "L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11C, 11C)
+11C = (11D, 11D)
+11D = (11Z, 11Z)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22Z, 22Z)
+";
+        assert_eq!(solve(input), "5".to_string());
+    }
+
+    #[test]
+    fn hit_before_cycle() {
+        let input = "L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22Z, 22Z)
+";
+        assert_eq!(solve(input), "1".to_string());
+    }
+
+    #[test]
+    fn never_in_step() {
+        let input = "L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22Z, 22Z)
+";
+        assert_eq!(solve(input), "never".to_string());
+    }
+
+    #[test]
+    fn unreachable_starts() {
+        let input = "L
+
+11A = (11Z, 11Z)
+11Z = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22A, 22A)
+33A = (33A, 33A)
+";
+        assert_eq!(
+            solve_with(input, None),
+            Err(WalkError::Unreachable(vec![
+                "22A".to_string(),
+                "33A".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn step_budget() {
+        let input = "L
+
+11A = (11B, 11B)
+11B = (11C, 11C)
+11C = (11Z, 11Z)
+11Z = (11A, 11A)
+";
+        assert_eq!(
+            solve_with(input, Some(2)),
+            Err(WalkError::OutOfSteps {
+                steps: 2,
+                node: "11C".to_string(),
+                instruction: 0,
+            })
+        );
+        assert_eq!(solve_with(input, Some(5)), Ok("3".to_string()));
+    }
+}
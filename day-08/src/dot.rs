@@ -0,0 +1,121 @@
+use std::fmt::Write;
+
+use crate::Network;
+
+/// Kept apart from the blue and red of the edges.
+const CYCLE_COLOURS: [&str; 6] = [
+    "darkorange",
+    "purple",
+    "forestgreen",
+    "gold",
+    "brown",
+    "deeppink",
+];
+
+/// Writes the network as a Graphviz digraph. `L` edges are solid and blue,
+/// `R` edges dashed and red. Start (`..A`) nodes are filled green and goal
+/// (`..Z`) nodes red.
+pub struct DotExport<'a> {
+    network: &'a Network,
+    cycles: Vec<Vec<usize>>,
+}
+
+impl<'a> DotExport<'a> {
+    pub fn new(network: &'a Network) -> Self {
+        DotExport {
+            network,
+            cycles: Vec::new(),
+        }
+    }
+
+    /// Outlines the nodes of every cycle in its own colour. A node on several
+    /// cycles keeps the colour of the first one.
+    pub fn with_cycles(mut self, cycles: Vec<Vec<usize>>) -> Self {
+        self.cycles = cycles;
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let names = self.network.names();
+        let mut cycle_colour = vec![None; names.len()];
+        for (cycle, colour) in self.cycles.iter().zip(CYCLE_COLOURS.iter().cycle()) {
+            for &node in cycle {
+                cycle_colour[node].get_or_insert(colour);
+            }
+        }
+
+        let mut dot = String::from("digraph network {\n    node [shape=ellipse];\n");
+        for (id, name) in names.iter().enumerate() {
+            let mut attributes = Vec::new();
+            if name.ends_with('A') {
+                attributes.push("style=filled, fillcolor=palegreen".to_string());
+            } else if name.ends_with('Z') {
+                attributes.push("style=filled, fillcolor=salmon".to_string());
+            }
+            if let Some(colour) = cycle_colour[id] {
+                attributes.push(format!("color={}, penwidth=3", colour));
+            }
+            if !attributes.is_empty() {
+                writeln!(dot, "    \"{}\" [{}];", name, attributes.join(", ")).unwrap();
+            }
+        }
+        for (id, name) in names.iter().enumerate() {
+            let (left, right) = self.network.links(id);
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"L\", color=blue];",
+                name,
+                self.network.name(left)
+            )
+            .unwrap();
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"R\", color=red, style=dashed];",
+                name,
+                self.network.name(right)
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cycle_nodes, parse_network};
+
+    #[test]
+    fn export_with_cycles() {
+        let input = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+XXX = (XXX, XXX)
+";
+        let network = parse_network(input).unwrap();
+        let start = network.id("11A").unwrap();
+        let cycle = cycle_nodes(&network, start);
+        assert_eq!(
+            DotExport::new(&network).with_cycles(vec![cycle]).render(),
+            "\
+digraph network {
+    node [shape=ellipse];
+    \"11A\" [style=filled, fillcolor=palegreen];
+    \"11B\" [color=darkorange, penwidth=3];
+    \"11Z\" [style=filled, fillcolor=salmon, color=darkorange, penwidth=3];
+    \"11A\" -> \"11B\" [label=\"L\", color=blue];
+    \"11A\" -> \"XXX\" [label=\"R\", color=red, style=dashed];
+    \"11B\" -> \"XXX\" [label=\"L\", color=blue];
+    \"11B\" -> \"11Z\" [label=\"R\", color=red, style=dashed];
+    \"11Z\" -> \"11B\" [label=\"L\", color=blue];
+    \"11Z\" -> \"XXX\" [label=\"R\", color=red, style=dashed];
+    \"XXX\" -> \"XXX\" [label=\"L\", color=blue];
+    \"XXX\" -> \"XXX\" [label=\"R\", color=red, style=dashed];
+}
+"
+        );
+    }
+}
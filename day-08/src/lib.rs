@@ -0,0 +1,327 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use nom::{
+    character::complete::{alphanumeric1, char, multispace0, multispace1, one_of, space0},
+    combinator::{eof, map},
+    multi::{many1, separated_list1},
+    sequence::{delimited, pair, separated_pair},
+    IResult,
+};
+
+pub mod dot;
+pub mod trace;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn parse(input: char) -> Direction {
+        match input {
+            'R' => Direction::Right,
+            'L' => Direction::Left,
+            _ => panic!("Invalid direction"),
+        }
+    }
+}
+
+pub struct Roundabout<T> {
+    pub data: Vec<T>,
+    pub current_index: usize,
+}
+
+impl<T> Roundabout<T> {
+    pub fn new(data: Vec<T>) -> Self {
+        Self {
+            data,
+            current_index: 0,
+        }
+    }
+    pub fn add(&mut self, item: T) {
+        self.data.push(item);
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&T> {
+        let result = self.data.get(self.current_index);
+        self.current_index += 1;
+        if self.current_index == self.data.len() {
+            self.current_index = 0;
+        }
+        result
+    }
+}
+
+/// The node map with every name interned to an id, so walking it only
+/// indexes into vectors.
+#[derive(Debug, PartialEq)]
+pub struct Network {
+    pub directions: Vec<Direction>,
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    links: Vec<(usize, usize)>,
+}
+
+impl Network {
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+    pub fn links(&self, id: usize) -> (usize, usize) {
+        self.links[id]
+    }
+    pub fn step(&self, id: usize, direction: Direction) -> usize {
+        let (left, right) = self.links[id];
+        match direction {
+            Direction::Left => left,
+            Direction::Right => right,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// The input stops following the grammar on this line.
+    Syntax(usize),
+    Duplicate(String),
+    /// `node` is linked from `from`, but never defined.
+    Undefined {
+        node: String,
+        from: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Syntax(line) => write!(f, "Invalid node map in line {}", line),
+            ParseError::Duplicate(node) => write!(f, "Node {} is defined more than once", node),
+            ParseError::Undefined { node, from } => {
+                write!(
+                    f,
+                    "Node {} is linked from {}, but never defined",
+                    node, from
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum WalkError {
+    /// These starts never get to a goal node.
+    Unreachable(Vec<String>),
+    /// The walk used up its step budget while standing on `node`, about to
+    /// follow instruction `instruction`.
+    OutOfSteps {
+        steps: u64,
+        node: String,
+        instruction: usize,
+    },
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalkError::Unreachable(starts) => {
+                write!(f, "No goal node reachable from {}", starts.join(", "))
+            }
+            WalkError::OutOfSteps {
+                steps,
+                node,
+                instruction,
+            } => write!(
+                f,
+                "Gave up after {} steps at {} before instruction {}",
+                steps, node, instruction
+            ),
+        }
+    }
+}
+
+/// Follows the directions from `start` until it stands on a goal node or a
+/// (node, instruction index) state repeats. Every state has exactly one
+/// successor, so once one repeats the walk goes round in circles for good.
+pub fn reaches_goal(network: &Network, start: usize, is_goal: impl Fn(usize) -> bool) -> bool {
+    let directions = &network.directions;
+    let mut seen = HashSet::new();
+    let mut current_node = start;
+    let mut index = 0;
+    while seen.insert((current_node, index)) {
+        if is_goal(current_node) {
+            return true;
+        }
+        let Some(&direction) = directions.get(index) else {
+            return false;
+        };
+        current_node = network.step(current_node, direction);
+        index = (index + 1) % directions.len();
+    }
+    false
+}
+
+/// The nodes of the cycle the walk from `start` ends up in, in the order it
+/// first visits them.
+pub fn cycle_nodes(network: &Network, start: usize) -> Vec<usize> {
+    let directions = &network.directions;
+    let mut first_seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut current_node = start;
+    let mut index = 0;
+    while let Some(&direction) = directions.get(index) {
+        if let Some(&cycle_start) = first_seen.get(&(current_node, index)) {
+            let mut seen = HashSet::new();
+            return states[cycle_start..]
+                .iter()
+                .map(|&(node, _)| node)
+                .filter(|&node| seen.insert(node))
+                .collect();
+        }
+        first_seen.insert((current_node, index), states.len());
+        states.push((current_node, index));
+        current_node = network.step(current_node, direction);
+        index = (index + 1) % directions.len();
+    }
+    Vec::new()
+}
+
+pub fn check_reachable(
+    network: &Network,
+    starts: &[usize],
+    is_goal: impl Fn(usize) -> bool,
+) -> Result<(), WalkError> {
+    let unreachable: Vec<String> = starts
+        .iter()
+        .filter(|&&start| !reaches_goal(network, start, &is_goal))
+        .map(|&start| network.name(start).to_string())
+        .collect();
+    if unreachable.is_empty() {
+        Ok(())
+    } else {
+        Err(WalkError::Unreachable(unreachable))
+    }
+}
+
+fn parse_directions(input: &str) -> IResult<&str, Vec<Direction>> {
+    many1(map(one_of("LR"), Direction::parse))(input)
+}
+
+fn parse_name(input: &str) -> IResult<&str, &str> {
+    delimited(space0, alphanumeric1, space0)(input)
+}
+
+/// A node's name with the names of its left and right neighbours.
+type NodeLine<'a> = (&'a str, (&'a str, &'a str));
+
+// NQH = (HPR, DTN)
+fn parse_node(input: &str) -> IResult<&str, NodeLine<'_>> {
+    let (input, name) = parse_name(input)?;
+    let (input, _) = char('=')(input)?;
+    let (input, links) = delimited(
+        pair(space0, char('(')),
+        separated_pair(parse_name, char(','), parse_name),
+        pair(char(')'), space0),
+    )(input)?;
+
+    Ok((input, (name, links)))
+}
+
+fn parse_map(input: &str) -> IResult<&str, (Vec<Direction>, Vec<NodeLine<'_>>)> {
+    let (input, directions) = delimited(multispace0, parse_directions, space0)(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, nodes) = separated_list1(multispace1, parse_node)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = eof(input)?;
+
+    Ok((input, (directions, nodes)))
+}
+
+pub fn parse_network(input: &str) -> Result<Network, ParseError> {
+    let (directions, nodes) = match parse_map(input) {
+        Ok((_, map)) => map,
+        Err(nom::Err::Error(err) | nom::Err::Failure(err)) => {
+            let offset = input.len() - err.input.trim_start().len();
+            return Err(ParseError::Syntax(
+                input[..offset].matches('\n').count() + 1,
+            ));
+        }
+        Err(nom::Err::Incomplete(_)) => unreachable!("Complete parsers only"),
+    };
+
+    let mut ids = HashMap::new();
+    let mut names = Vec::new();
+    for &(name, _) in &nodes {
+        if ids.insert(name.to_string(), names.len()).is_some() {
+            return Err(ParseError::Duplicate(name.to_string()));
+        }
+        names.push(name.to_string());
+    }
+    let mut links = Vec::new();
+    for &(name, (left, right)) in &nodes {
+        let id = |node: &str| {
+            ids.get(node).copied().ok_or_else(|| ParseError::Undefined {
+                node: node.to_string(),
+                from: name.to_string(),
+            })
+        };
+        links.push((id(left)?, id(right)?));
+    }
+
+    Ok(Network {
+        directions,
+        names,
+        ids,
+        links,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_names_and_whitespace() {
+        let input =
+            "\r\n  LR  \r\n\r\nSTART1 =(  LEFT , START1)\r\n  LEFT=(LEFT,START1 )  \r\n\r\n";
+        let network = parse_network(input).unwrap();
+        assert_eq!(network.directions, vec![Direction::Left, Direction::Right]);
+        assert_eq!(network.names(), ["START1", "LEFT"]);
+        let start = network.id("START1").unwrap();
+        assert_eq!(
+            network.step(start, Direction::Left),
+            network.id("LEFT").unwrap()
+        );
+        assert_eq!(network.step(start, Direction::Right), start);
+    }
+
+    #[test]
+    fn invalid_maps() {
+        assert_eq!(
+            parse_network("L\n\nAAA = (BBB, AAA)\nBBB = (AAA, AAA)\nAAA = (BBB, BBB)"),
+            Err(ParseError::Duplicate("AAA".to_string()))
+        );
+        assert_eq!(
+            parse_network("L\n\nAAA = (BBB, CCC)\nBBB = (AAA, AAA)"),
+            Err(ParseError::Undefined {
+                node: "CCC".to_string(),
+                from: "AAA".to_string()
+            })
+        );
+        assert_eq!(
+            parse_network("L\n\nAAA = (BBB, AAA)\nBBB = AAA, AAA\n"),
+            Err(ParseError::Syntax(4))
+        );
+        assert_eq!(
+            parse_network("LX\n\nAAA = (AAA, AAA)"),
+            Err(ParseError::Syntax(1))
+        );
+    }
+}
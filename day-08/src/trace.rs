@@ -0,0 +1,119 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::Network;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceFormat {
+    Full,
+    Summary,
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(TraceFormat::Full),
+            "summary" => Ok(TraceFormat::Summary),
+            _ => Err(format!("Unknown trace format: {}, use full or summary", s)),
+        }
+    }
+}
+
+/// A node id with the index of the instruction to follow from it.
+type State = (usize, usize);
+
+/// The state of a walk before every step, and the state it ends in.
+#[derive(Debug, Default)]
+pub struct Trace {
+    states: Vec<State>,
+}
+
+impl Trace {
+    pub fn record(&mut self, node: usize, instruction: usize) {
+        self.states.push((node, instruction));
+    }
+
+    pub fn states(&self) -> &[State] {
+        &self.states
+    }
+
+    pub fn format(&self, network: &Network, format: TraceFormat) -> String {
+        match format {
+            TraceFormat::Full => self.format_full(network),
+            TraceFormat::Summary => self.format_summary(network),
+        }
+    }
+
+    pub fn format_full(&self, network: &Network) -> String {
+        let mut output = String::from("step\tinstruction\tnode\n");
+        for (step, &(node, instruction)) in self.states.iter().enumerate() {
+            writeln!(output, "{}\t{}\t{}", step, instruction, network.name(node)).unwrap();
+        }
+        output
+    }
+
+    /// One line per pass through the instructions, where a pass visiting the
+    /// same nodes as the one before it is folded into that line.
+    pub fn format_summary(&self, network: &Network) -> String {
+        let mut passes: Vec<(usize, &[State], usize)> = Vec::new();
+        let mut start = 0;
+        for step in 1..=self.states.len() {
+            if step < self.states.len() && self.states[step].1 != 0 {
+                continue;
+            }
+            let pass = &self.states[start..step];
+            match passes.last_mut() {
+                Some((_, previous, repeats)) if *previous == pass => *repeats += 1,
+                _ => passes.push((start, pass, 1)),
+            }
+            start = step;
+        }
+
+        let mut output = String::new();
+        for (start, pass, repeats) in passes {
+            let (first, last) = (pass[0], pass[pass.len() - 1]);
+            write!(
+                output,
+                "steps {}-{}: {} .. {} (instructions {}-{})",
+                start,
+                start + pass.len() * repeats - 1,
+                network.name(first.0),
+                network.name(last.0),
+                first.1,
+                last.1
+            )
+            .unwrap();
+            if repeats > 1 {
+                write!(output, " x{}", repeats).unwrap();
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_network;
+
+    #[test]
+    fn summary_folds_repeated_passes() {
+        let network = parse_network("LR\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\n").unwrap();
+        let (a, b) = (network.id("AAA").unwrap(), network.id("BBB").unwrap());
+        let mut trace = Trace::default();
+        for &(node, instruction) in &[(b, 1), (a, 0), (b, 1), (a, 0), (b, 1), (a, 0)] {
+            trace.record(node, instruction);
+        }
+        assert_eq!(
+            trace.format_summary(&network),
+            "\
+steps 0-0: BBB .. BBB (instructions 1-1)
+steps 1-4: AAA .. BBB (instructions 0-1) x2
+steps 5-5: AAA .. AAA (instructions 0-0)
+"
+        );
+    }
+}
@@ -1,53 +1,55 @@
+use std::env;
+
+use day_09::{fit_all, parse, Direction, HistoryError, Mode, Polynomial};
+
+const USAGE: &str = "Usage: part2 [--lenient]";
+
 fn main() {
     println!("Part 2");
     let input = include_str!("../../input.txt");
-    let result = solve(input);
+    let mut mode = Mode::Strict;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--lenient" => mode = Mode::Lenient,
+            _ => panic!("{}", USAGE),
+        }
+    }
+    let result = match mode {
+        Mode::Strict => solve(input),
+        Mode::Lenient => {
+            let (result, skipped) = solve_with(input, mode).unwrap_or_else(|err| panic!("{}", err));
+            println!("Skipped {} histories that are not polynomials", skipped);
+            result
+        }
+    };
     println!("Result = {}", result);
 }
 
 fn solve(input: &str) -> String {
-    let datas = parse(input);
-    calculate_all_values(&datas).to_string()
+    let (result, _) = solve_with(input, Mode::Strict).unwrap_or_else(|err| panic!("{}", err));
+    result
 }
 
-fn parse(input: &str) -> Vec<Vec<i32>> {
-    let mut result = Vec::new();
-    for line in input.lines() {
-        let mut row = Vec::new();
-        for value in line.split_whitespace() {
-            row.push(value.parse().unwrap());
-        }
-        result.push(row);
-    }
-    result
+fn solve_with(input: &str, mode: Mode) -> Result<(String, usize), HistoryError> {
+    let datas = parse(input);
+    let (polynomials, skipped) = fit_all(&datas, mode)?;
+    Ok((calculate_all_values(&polynomials).to_string(), skipped))
 }
 
-fn calculate_all_values(values: &[Vec<i32>]) -> i32 {
+fn calculate_all_values(polynomials: &[Polynomial]) -> i128 {
     let mut result = 0;
 
-    for row in values {
-        result += calculate_next_value(row);
+    for polynomial in polynomials {
+        result += calculate_next_value(polynomial);
     }
 
     result
 }
 
-fn calculate_next_value(values: &[i32]) -> i32 {
-    let mut result = 0;
-    let mut stages: Vec<Vec<i32>> = vec![values.to_vec()];
-    while stages.last().unwrap().iter().any(|&x| x != 0) {
-        let stage = stages.last().unwrap();
-        let mut new_stage = Vec::new();
-        for i in 0..stage.len() - 1 {
-            new_stage.push(*stage.get(i + 1).unwrap() - *stage.get(i).unwrap());
-        }
-        stages.push(new_stage);
-    }
-    for stage in stages.iter().rev() {
-        result = stage.first().unwrap() - result;
-    }
-    result
+fn calculate_next_value(polynomial: &Polynomial) -> i128 {
+    polynomial.extrapolate(Direction::Backward, 1)[0]
 }
+
 #[cfg(test)]
 mod tests {
     use crate::solve;
@@ -62,4 +64,3 @@ mod tests {
         assert_eq!(output, "2".to_string());
     }
 }
-
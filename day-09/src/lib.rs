@@ -0,0 +1,226 @@
+use std::fmt;
+
+use num::rational::Ratio;
+
+/// The histories with the number of the line they are in, blank lines are
+/// skipped.
+pub fn parse(input: &str) -> Vec<(usize, Vec<i128>)> {
+    let mut result = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut row = Vec::new();
+        for value in line.split_whitespace() {
+            row.push(value.parse().unwrap());
+        }
+        result.push((idx + 1, row));
+    }
+    result
+}
+
+/// The differences of a history never became all zero before running out of
+/// terms, so there is nothing to extrapolate from.
+#[derive(Debug, PartialEq)]
+pub struct NotPolynomial {
+    /// The deepest difference stage that still had a value in it.
+    pub depth: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct HistoryError {
+    pub line: usize,
+    pub depth: usize,
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "History in line {} is not a polynomial, its differences are not zero at stage {}",
+            self.line, self.depth
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Fail on the first history that is not a polynomial.
+    Strict,
+    /// Skip the histories that are not polynomials.
+    Lenient,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// After the last value of the history.
+    Forward,
+    /// Before the first value of the history.
+    Backward,
+}
+
+/// The polynomial through a history, with the first value at index 0. It is
+/// kept in Newton form: `differences[k]` is the first value of the k-th
+/// difference stage, and the value at `x` is the sum of
+/// `differences[k] * binomial(x, k)`.
+#[derive(Debug, PartialEq)]
+pub struct Polynomial {
+    differences: Vec<i128>,
+    len: usize,
+}
+
+impl Polynomial {
+    pub fn fit(values: &[i128]) -> Result<Self, NotPolynomial> {
+        let mut differences = Vec::new();
+        let mut stage = values.to_vec();
+        while stage.iter().any(|&x| x != 0) || stage.is_empty() {
+            if stage.is_empty() {
+                return Err(NotPolynomial {
+                    depth: differences.len().saturating_sub(1),
+                });
+            }
+            differences.push(stage[0]);
+            stage = stage.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        }
+        Ok(Polynomial {
+            differences,
+            len: values.len(),
+        })
+    }
+
+    /// The zero polynomial counts as degree 0.
+    pub fn degree(&self) -> usize {
+        self.differences.len().saturating_sub(1)
+    }
+
+    pub fn evaluate(&self, x: i128) -> i128 {
+        let mut result = 0;
+        let mut binomial = 1;
+        for (k, &difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                // binomial(x, k) = binomial(x, k - 1) * (x - k + 1) / k,
+                // which divides evenly for every integer x
+                binomial = binomial * (x - k as i128 + 1) / k as i128;
+            }
+            result += difference * binomial;
+        }
+        result
+    }
+
+    /// The next `count` values in `direction`, nearest to the history first.
+    pub fn extrapolate(&self, direction: Direction, count: usize) -> Vec<i128> {
+        (0..count as i128)
+            .map(|step| match direction {
+                Direction::Forward => self.evaluate(self.len as i128 + step),
+                Direction::Backward => self.evaluate(-1 - step),
+            })
+            .collect()
+    }
+
+    /// The coefficients in powers of `x`, starting with the constant term.
+    pub fn coefficients(&self) -> Vec<Ratio<i128>> {
+        let mut coefficients = vec![Ratio::from_integer(0); self.degree() + 1];
+        // x * (x - 1) * ... * (x - k + 1), in powers of x
+        let mut falling = vec![1i128];
+        let mut factorial = 1;
+        for (k, &difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                factorial *= k as i128;
+                let mut next = vec![0; falling.len() + 1];
+                for (power, &coefficient) in falling.iter().enumerate() {
+                    next[power + 1] += coefficient;
+                    next[power] -= coefficient * (k as i128 - 1);
+                }
+                falling = next;
+            }
+            for (power, &coefficient) in falling.iter().enumerate() {
+                coefficients[power] += Ratio::new(difference * coefficient, factorial);
+            }
+        }
+        coefficients
+    }
+}
+
+/// Fits every history and returns the polynomials with the number of skipped
+/// histories. Only `Mode::Lenient` skips any.
+pub fn fit_all(
+    histories: &[(usize, Vec<i128>)],
+    mode: Mode,
+) -> Result<(Vec<Polynomial>, usize), HistoryError> {
+    let mut polynomials = Vec::new();
+    let mut skipped = 0;
+    for (line, history) in histories {
+        match (Polynomial::fit(history), mode) {
+            (Ok(polynomial), _) => polynomials.push(polynomial),
+            (Err(_), Mode::Lenient) => skipped += 1,
+            (Err(NotPolynomial { depth }), Mode::Strict) => {
+                return Err(HistoryError { line: *line, depth })
+            }
+        }
+    }
+    Ok((polynomials, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extrapolate_both_ways() {
+        let polynomial = Polynomial::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(polynomial.degree(), 3);
+        assert_eq!(
+            polynomial.extrapolate(Direction::Forward, 3),
+            vec![68, 101, 146]
+        );
+        assert_eq!(polynomial.extrapolate(Direction::Backward, 2), vec![5, -4]);
+    }
+
+    #[test]
+    fn triangular_coefficients() {
+        let polynomial = Polynomial::fit(&[1, 3, 6, 10, 15, 21]).unwrap();
+        assert_eq!(polynomial.degree(), 2);
+        assert_eq!(
+            polynomial.coefficients(),
+            vec![Ratio::new(1, 1), Ratio::new(3, 2), Ratio::new(1, 2)]
+        );
+        // (x + 1) * (x + 2) / 2, far beyond what fits into an i32
+        assert_eq!(polynomial.evaluate(1_000_000), 500_001_500_001);
+        assert_eq!(polynomial.evaluate(-3), 1);
+    }
+
+    #[test]
+    fn constant_histories() {
+        assert_eq!(
+            Polynomial::fit(&[0, 0, 0]).unwrap().coefficients(),
+            vec![Ratio::from_integer(0)]
+        );
+        let polynomial = Polynomial::fit(&[7, 7]).unwrap();
+        assert_eq!(polynomial.degree(), 0);
+        assert_eq!(polynomial.evaluate(-100), 7);
+    }
+
+    #[test]
+    fn not_polynomial() {
+        assert_eq!(
+            Polynomial::fit(&[1, 2, 4, 8, 16]),
+            Err(NotPolynomial { depth: 4 })
+        );
+        assert_eq!(Polynomial::fit(&[]), Err(NotPolynomial { depth: 0 }));
+
+        let histories = parse("0 3 6 9 12 15\n\n1 2 4 8 16\n1 3 6 10 15 21\n");
+        assert_eq!(
+            fit_all(&histories, Mode::Strict),
+            Err(HistoryError { line: 3, depth: 4 })
+        );
+        let (polynomials, skipped) = fit_all(&histories, Mode::Lenient).unwrap();
+        assert_eq!((polynomials.len(), skipped), (2, 1));
+
+        // Blank lines are no histories at all
+        let histories = parse("0 3 6 9 12 15\n\n1 3 6 10 15 21\n");
+        assert_eq!(
+            fit_all(&histories, Mode::Strict).map(|(_, skipped)| skipped),
+            Ok(0)
+        );
+    }
+}
@@ -1,230 +1,30 @@
 // Special Thanks to "Bewelge" for the Idea to skip certain Pipes
 // https://www.reddit.com/r/adventofcode/comments/18evyu9/comment/kcsal0o/?utm_source=share&utm_medium=web3x&utm_name=web3xcss&utm_term=1&utm_content=share_button
 
+use std::env;
+
+use day_10::{count_enclosed, parse, Path, Renderer, Tile, TileMap};
+
 fn main() {
     println!("Part 2");
     let input = include_str!("../../input.txt");
-    let result = solve(input);
-    println!("Result = {}", result);
-}
-
-#[derive(Debug)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
-impl Direction {
-    fn get_indices(&self) -> (isize, isize) {
-        use Direction::*;
-        match self {
-            North => (0, -1),
-            South => (0, 1),
-            East => (1, 0),
-            West => (-1, 0),
-        }
-    }
-    fn iter() -> impl Iterator<Item = Direction> {
-        use Direction::*;
-        vec![North, South, East, West].into_iter()
-    }
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Tile {
-    StartingPosition,
-    NorthSouth,
-    EastWest,
-    NorthEast,
-    NorthWest,
-    SouthEast,
-    SouthWest,
-    Ground,
-}
-
-impl From<char> for Tile {
-    fn from(c: char) -> Self {
-        use Tile::*;
-        match c {
-            '.' => Ground,
-            '|' => NorthSouth,
-            '-' => EastWest,
-            'L' => NorthEast,
-            'J' => NorthWest,
-            '7' => SouthWest,
-            'F' => SouthEast,
-            'S' => StartingPosition,
-            _ => panic!("Unknown tile: {}", c),
-        }
-    }
-}
-
-impl Tile {
-    fn are_connected(&self, walk_direction: Direction, other: &Self) -> bool {
-        match self {
-            Tile::StartingPosition => match walk_direction {
-                Direction::North => {
-                    matches!(other, Tile::NorthSouth | Tile::SouthEast | Tile::SouthWest)
-                }
-                Direction::South => {
-                    matches!(other, Tile::NorthSouth | Tile::NorthEast | Tile::NorthWest)
-                }
-                Direction::East => {
-                    matches!(other, Tile::EastWest | Tile::NorthWest | Tile::SouthWest)
-                }
-                Direction::West => {
-                    matches!(other, Tile::EastWest | Tile::NorthEast | Tile::SouthEast)
-                }
-            },
-            Tile::NorthSouth => match walk_direction {
-                Direction::North => {
-                    matches!(other, Tile::NorthSouth | Tile::SouthEast | Tile::SouthWest)
-                }
-                Direction::South => {
-                    matches!(other, Tile::NorthSouth | Tile::NorthEast | Tile::NorthWest)
-                }
-                _ => false,
-            },
-            Tile::EastWest => match walk_direction {
-                Direction::East => {
-                    matches!(other, Tile::EastWest | Tile::NorthWest | Tile::SouthWest)
-                }
-                Direction::West => {
-                    matches!(other, Tile::EastWest | Tile::NorthEast | Tile::SouthEast)
-                }
-                _ => false,
-            },
-            Tile::NorthEast => match walk_direction {
-                Direction::North => {
-                    matches!(other, Tile::NorthSouth | Tile::SouthWest | Tile::SouthEast)
-                }
-                Direction::East => {
-                    matches!(other, Tile::EastWest | Tile::NorthWest | Tile::SouthWest)
-                }
-                _ => false,
-            },
-            Tile::NorthWest => match walk_direction {
-                Direction::North => {
-                    matches!(other, Tile::NorthSouth | Tile::SouthEast | Tile::SouthWest)
-                }
-                Direction::West => {
-                    matches!(other, Tile::EastWest | Tile::NorthEast | Tile::SouthEast)
-                }
-                _ => false,
-            },
-            Tile::SouthEast => match walk_direction {
-                Direction::South => {
-                    matches!(other, Tile::NorthSouth | Tile::NorthWest | Tile::NorthEast)
-                }
-                Direction::East => {
-                    matches!(other, Tile::EastWest | Tile::NorthWest | Tile::SouthWest)
-                }
-                _ => false,
-            },
-            Tile::SouthWest => match walk_direction {
-                Direction::South => {
-                    matches!(other, Tile::NorthSouth | Tile::NorthEast | Tile::NorthWest)
-                }
-                Direction::West => {
-                    matches!(other, Tile::EastWest | Tile::NorthEast | Tile::SouthEast)
-                }
-                _ => false,
-            },
-            _ => false,
-        }
-    }
-}
-
-struct TileMap {
-    tiles: Vec<Vec<Tile>>,
-}
-
-impl TileMap {
-    fn get(&self, position: (usize, usize)) -> Option<&Tile> {
-        match &self.tiles.get(position.1) {
-            Some(row) => row.get(position.0),
-            None => None,
-        }
-    }
-}
-
-struct Path {
-    tiles: TileMap,
-    starting_position: (usize, usize),
-    current_position: (usize, usize),
-    path: Vec<(usize, usize)>,
-}
-
-impl Path {
-    fn with(tiles: Vec<Vec<Tile>>) -> Self {
-        let starting_position = Self::get_starting_position(&tiles);
-        Path {
-            tiles: TileMap { tiles },
-            starting_position,
-            current_position: (0, 0),
-            path: Vec::new(),
-        }
-    }
-    fn setup(&mut self) {
-        let (x, y) = self.starting_position;
-        self.path.push((x, y));
-        for direction in Direction::iter() {
-            let (dx, dy) = direction.get_indices();
-            let pos = (x.saturating_add_signed(dx), y.saturating_add_signed(dy));
-            let tile = self.tiles.get(pos).expect("Should be there");
-            if self
-                .tiles
-                .get(self.starting_position)
-                .expect("Should be there")
-                .are_connected(direction, tile)
-            {
-                self.current_position = pos;
-                self.path.push(pos);
-                break;
-            }
-        }
-    }
-    fn find_path(&mut self) {
-        while self.tiles.get(self.current_position).unwrap() != &Tile::StartingPosition {
-            if let Some(next_pipe) = self.get_next_pipe() {
-                self.current_position = next_pipe;
-            } else {
-                break;
-            }
-        }
-    }
-    fn get_next_pipe(&mut self) -> Option<(usize, usize)> {
-        let (x, y) = self.current_position;
-        for direction in Direction::iter() {
-            let (dx, dy) = direction.get_indices();
-            let pos = (x.saturating_add_signed(dx), y.saturating_add_signed(dy));
-            let tile = match self.tiles.get(pos) {
-                Some(tile) => tile,
-                None => continue,
-            };
-            let old_tile = self
-                .tiles
-                .get(self.current_position)
-                .expect("Should be there");
-            if old_tile.are_connected(direction, tile) && !self.path.contains(&pos) {
-                self.path.push(pos);
-                return Some(pos);
-            }
+    let mut algorithm = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--render" => print!("{}", render(input)),
+            "scanline" => algorithm = Some(Algorithm::Scanline),
+            "shoelace" => algorithm = Some(Algorithm::Shoelace),
+            other => panic!(
+                "Unknown argument: {}, use scanline, shoelace or --render",
+                other
+            ),
         }
-        None
-    }
-    fn get_starting_position(tiles: &[Vec<Tile>]) -> (usize, usize) {
-        for (y, row) in tiles.iter().enumerate() {
-            for (x, tile) in row.iter().enumerate() {
-                if let Tile::StartingPosition = tile {
-                    return (x, y);
-                }
-            }
-        }
-        panic!("No starting position found");
     }
+    let result = match algorithm {
+        None => solve(input),
+        Some(algorithm) => solve_with(input, algorithm),
+    };
+    println!("Result = {}", result);
 }
 
 enum Mode {
@@ -232,6 +32,15 @@ enum Mode {
     Out,
 }
 
+#[derive(Debug, Clone, Copy)]
+enum Algorithm {
+    /// Walk every row and flip between inside and outside on each crossing.
+    Scanline,
+    /// Take the loop's area with the shoelace formula and get the tiles
+    /// inside with Pick's theorem.
+    Shoelace,
+}
+
 #[derive(Debug, Clone, Default, PartialEq)]
 enum AreaType {
     Pipe {
@@ -246,16 +55,19 @@ enum AreaType {
 struct EncloseSearcher {
     tile_map: TileMap,
     path: Vec<(usize, usize)>,
+    vertices: Vec<(usize, usize)>,
     enclosing_map: Vec<Vec<AreaType>>,
     mode: Mode,
 }
 
 impl EncloseSearcher {
-    fn with(tiles: Vec<Vec<Tile>>, path: Vec<(usize, usize)>) -> Self {
-        let enclosing_map = vec![vec![AreaType::Unknown; tiles[0].len()]; tiles.len()];
+    fn with(tile_map: TileMap, path: Vec<(usize, usize)>, vertices: Vec<(usize, usize)>) -> Self {
+        let enclosing_map =
+            vec![vec![AreaType::Unknown; tile_map.tiles[0].len()]; tile_map.tiles.len()];
         EncloseSearcher {
-            tile_map: TileMap { tiles },
+            tile_map,
             path,
+            vertices,
             enclosing_map,
             mode: Mode::Out,
         }
@@ -272,6 +84,9 @@ impl EncloseSearcher {
         }
         result
     }
+    fn count_enclosed_shoelace(&self) -> u32 {
+        count_enclosed(&self.vertices, self.path.len()) as u32
+    }
     fn fill_enclosing_map(&mut self) {
         use AreaType::*;
         use Mode::*;
@@ -290,12 +105,11 @@ impl EncloseSearcher {
                         Within => *tile = Enclosed,
                         Out => *tile = Outerspace,
                     },
-                    Pipe { tile_type } => match tile_type {
-                        NorthSouth | SouthWest | SouthEast | StartingPosition => match self.mode {
-                            Within => self.mode = Out,
-                            Out => self.mode = Within,
-                        },
-                        _ => {}
+                    Pipe {
+                        tile_type: NorthSouth | SouthWest | SouthEast,
+                    } => match self.mode {
+                        Within => self.mode = Out,
+                        Out => self.mode = Within,
                     },
                     _ => {}
                 }
@@ -315,32 +129,47 @@ impl EncloseSearcher {
 }
 
 fn solve(input: &str) -> String {
-    let tiles = parse(input);
+    solve_with(input, Algorithm::Scanline)
+}
+
+fn solve_with(input: &str, algorithm: Algorithm) -> String {
+    let tiles = parse(input).expect("Starting position should be part of a loop");
     let mut path = Path::with(tiles);
     path.setup();
-    path.find_path();
-    let mut enclose_searcher = EncloseSearcher::with(path.tiles.tiles, path.path);
-    let enclosed_areas = enclose_searcher.find_enclosed_areas();
+    path.find_path()
+        .expect("Starting position should be part of a loop");
+    let vertices = path.get_vertices();
+    let mut enclose_searcher = EncloseSearcher::with(path.tiles, path.path, vertices);
+    let enclosed_areas = match algorithm {
+        Algorithm::Scanline => enclose_searcher.find_enclosed_areas(),
+        Algorithm::Shoelace => enclose_searcher.count_enclosed_shoelace(),
+    };
     //enclose_searcher.print();
     enclosed_areas.to_string()
 }
 
-fn parse(input: &str) -> Vec<Vec<Tile>> {
-    let mut result = Vec::new();
-    for line in input.lines() {
-        let mut row = Vec::new();
-        for c in line.chars() {
-            let tile = Tile::from(c);
-            row.push(tile);
-        }
-        result.push(row);
-    }
-    result
+fn render(input: &str) -> String {
+    let tiles = parse(input).expect("Starting position should be part of a loop");
+    let mut path = Path::with(tiles);
+    path.setup();
+    path.find_path()
+        .expect("Starting position should be part of a loop");
+    let vertices = path.get_vertices();
+    let mut enclose_searcher = EncloseSearcher::with(path.tiles, path.path, vertices);
+    enclose_searcher.fill_enclosing_map();
+    let enclosed = enclose_searcher
+        .enclosing_map
+        .iter()
+        .map(|row| row.iter().map(|tile| tile == &AreaType::Enclosed).collect())
+        .collect();
+    Renderer::new(&enclose_searcher.tile_map, &enclose_searcher.path)
+        .with_enclosed(enclosed)
+        .render()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::solve;
+    use crate::{solve, solve_with, Algorithm};
 
     #[test]
     fn example_input() {
@@ -390,5 +219,64 @@ L7JLJL-JLJLJL--JLJ.L";
         let output = solve(input);
         assert_eq!(output, "10".to_string());
     }
-}
 
+    #[test]
+    fn starting_position_not_crossing() {
+        // `S` is an `L` here, which must not flip the scanline
+        let input = "...........
+.F-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.S--J.L--J.
+...........
+";
+        assert_eq!(solve_with(input, Algorithm::Scanline), "4".to_string());
+        assert_eq!(solve_with(input, Algorithm::Shoelace), "4".to_string());
+    }
+
+    #[test]
+    fn algorithms_agree() {
+        let inputs = [
+            "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........
+",
+            ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...",
+            "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L",
+            include_str!("../../input.txt"),
+        ];
+        for input in inputs {
+            assert_eq!(
+                solve_with(input, Algorithm::Scanline),
+                solve_with(input, Algorithm::Shoelace)
+            );
+        }
+    }
+}
@@ -0,0 +1,635 @@
+use std::fmt;
+use std::io::{self, IsTerminal};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    pub fn get_indices(&self) -> (isize, isize) {
+        use Direction::*;
+        match self {
+            North => (0, -1),
+            South => (0, 1),
+            East => (1, 0),
+            West => (-1, 0),
+        }
+    }
+    pub fn opposite(&self) -> Direction {
+        use Direction::*;
+        match self {
+            North => South,
+            South => North,
+            East => West,
+            West => East,
+        }
+    }
+    pub fn iter() -> impl Iterator<Item = Direction> {
+        use Direction::*;
+        vec![North, South, East, West].into_iter()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Tile {
+    StartingPosition,
+    NorthSouth,
+    EastWest,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+    Ground,
+}
+
+impl From<char> for Tile {
+    fn from(c: char) -> Self {
+        use Tile::*;
+        match c {
+            '.' => Ground,
+            '|' => NorthSouth,
+            '-' => EastWest,
+            'L' => NorthEast,
+            'J' => NorthWest,
+            '7' => SouthWest,
+            'F' => SouthEast,
+            'S' => StartingPosition,
+            _ => panic!("Unknown tile: {}", c),
+        }
+    }
+}
+
+impl Tile {
+    /// The two directions a pipe opens to, nothing for ground and the start.
+    pub fn exits(&self) -> &'static [Direction] {
+        use Direction::*;
+        match self {
+            Tile::NorthSouth => &[North, South],
+            Tile::EastWest => &[East, West],
+            Tile::NorthEast => &[North, East],
+            Tile::NorthWest => &[North, West],
+            Tile::SouthEast => &[South, East],
+            Tile::SouthWest => &[South, West],
+            Tile::StartingPosition | Tile::Ground => &[],
+        }
+    }
+    pub fn to_char(&self) -> char {
+        match self {
+            Tile::Ground => '.',
+            Tile::NorthSouth => '|',
+            Tile::EastWest => '-',
+            Tile::NorthEast => 'L',
+            Tile::NorthWest => 'J',
+            Tile::SouthWest => '7',
+            Tile::SouthEast => 'F',
+            Tile::StartingPosition => 'S',
+        }
+    }
+    pub fn to_box_char(&self) -> char {
+        match self {
+            Tile::Ground => ' ',
+            Tile::NorthSouth => '│',
+            Tile::EastWest => '─',
+            Tile::NorthEast => '└',
+            Tile::NorthWest => '┘',
+            Tile::SouthWest => '┐',
+            Tile::SouthEast => '┌',
+            Tile::StartingPosition => 'S',
+        }
+    }
+    /// The pipe that opens to exactly these two directions.
+    pub fn with_exits(a: Direction, b: Direction) -> Option<Tile> {
+        [
+            Tile::NorthSouth,
+            Tile::EastWest,
+            Tile::NorthEast,
+            Tile::NorthWest,
+            Tile::SouthEast,
+            Tile::SouthWest,
+        ]
+        .into_iter()
+        .find(|tile| a != b && tile.exits().contains(&a) && tile.exits().contains(&b))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StartError {
+    Missing,
+    /// `S` has to connect to exactly two neighbours to be part of a loop.
+    Connections(usize),
+    /// The pipes leaving `S` run into a dead end instead of coming back.
+    Unclosed,
+}
+
+impl fmt::Display for StartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartError::Missing => write!(f, "No starting position found"),
+            StartError::Connections(count) => write!(
+                f,
+                "Starting position connects to {} neighbours instead of 2",
+                count
+            ),
+            StartError::Unclosed => write!(f, "The pipes from the starting position don't close"),
+        }
+    }
+}
+
+pub struct TileMap {
+    pub tiles: Vec<Vec<Tile>>,
+    pub starting_position: (usize, usize),
+}
+
+impl TileMap {
+    pub fn get(&self, position: (usize, usize)) -> Option<&Tile> {
+        match &self.tiles.get(position.1) {
+            Some(row) => row.get(position.0),
+            None => None,
+        }
+    }
+    pub fn neighbour(
+        &self,
+        (x, y): (usize, usize),
+        direction: Direction,
+    ) -> Option<(usize, usize)> {
+        let (dx, dy) = direction.get_indices();
+        let pos = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.get(pos).map(|_| pos)
+    }
+    /// Whether the pipe at `pos` opens to `direction` and the neighbour there
+    /// opens back.
+    pub fn is_linked(&self, pos: (usize, usize), direction: Direction) -> bool {
+        let opens = self
+            .get(pos)
+            .is_some_and(|tile| tile.exits().contains(&direction));
+        opens
+            && self
+                .neighbour(pos, direction)
+                .and_then(|other| self.get(other))
+                .is_some_and(|tile| tile.exits().contains(&direction.opposite()))
+    }
+    /// Every closed loop in the map together with the pipes that aren't part
+    /// of any loop. Each pipe has two exits, so pipes linked on both ends
+    /// either form loops or run into a dead end.
+    pub fn find_loops(&self) -> LoopReport {
+        let mut visited: Vec<Vec<bool>> = self
+            .tiles
+            .iter()
+            .map(|row| vec![false; row.len()])
+            .collect();
+        let mut report = LoopReport::default();
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if visited[y][x] || tile.exits().is_empty() {
+                    continue;
+                }
+                let component = self.collect_linked((x, y), &mut visited);
+                let closed = component.iter().all(|&pos| {
+                    let exits = self.get(pos).expect("Should be there").exits();
+                    exits.iter().all(|&exit| self.is_linked(pos, exit))
+                });
+                if closed {
+                    report.loops.push(PipeLoop::with(self.trace_loop((x, y))));
+                } else {
+                    report.fragments.push(component);
+                }
+            }
+        }
+        report
+    }
+    fn collect_linked(
+        &self,
+        start: (usize, usize),
+        visited: &mut [Vec<bool>],
+    ) -> Vec<(usize, usize)> {
+        let mut component = Vec::new();
+        let mut stack = vec![start];
+        visited[start.1][start.0] = true;
+        while let Some(pos) = stack.pop() {
+            component.push(pos);
+            for &exit in self.get(pos).expect("Should be there").exits() {
+                if !self.is_linked(pos, exit) {
+                    continue;
+                }
+                let (x, y) = self
+                    .neighbour(pos, exit)
+                    .expect("Linked pipes are on the map");
+                if !visited[y][x] {
+                    visited[y][x] = true;
+                    stack.push((x, y));
+                }
+            }
+        }
+        component
+    }
+    fn trace_loop(&self, start: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut result = vec![start];
+        let mut direction = self.get(start).expect("Should be there").exits()[0];
+        let mut pos = start;
+        loop {
+            pos = self.neighbour(pos, direction).expect("Loops are closed");
+            if pos == start {
+                return result;
+            }
+            result.push(pos);
+            let entry = direction.opposite();
+            direction = *self
+                .get(pos)
+                .expect("Should be there")
+                .exits()
+                .iter()
+                .find(|&&exit| exit != entry)
+                .expect("Pipes have two exits");
+        }
+    }
+    /// Replaces `S` with the pipe it has to be to connect its two neighbours.
+    fn resolve_starting_position(&mut self) -> Result<(), StartError> {
+        let start = self.starting_position;
+        let connections: Vec<Direction> = Direction::iter()
+            .filter(|&direction| {
+                self.neighbour(start, direction)
+                    .and_then(|pos| self.get(pos))
+                    .is_some_and(|tile| tile.exits().contains(&direction.opposite()))
+            })
+            .collect();
+        let tile = match connections[..] {
+            [a, b] => Tile::with_exits(a, b).expect("Two different directions"),
+            _ => return Err(StartError::Connections(connections.len())),
+        };
+        self.tiles[start.1][start.0] = tile;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LoopReport {
+    pub loops: Vec<PipeLoop>,
+    /// Groups of linked pipes that don't close into a loop.
+    pub fragments: Vec<Vec<(usize, usize)>>,
+}
+
+#[derive(Debug)]
+pub struct PipeLoop {
+    /// The loop's tiles in walking order.
+    pub tiles: Vec<(usize, usize)>,
+    /// Top left and bottom right corner.
+    pub bounding_box: ((usize, usize), (usize, usize)),
+    /// Number of tiles inside the loop that aren't part of it.
+    pub enclosed: usize,
+}
+
+impl PipeLoop {
+    fn with(tiles: Vec<(usize, usize)>) -> Self {
+        let min_x = tiles.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = tiles.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let max_x = tiles.iter().map(|&(x, _)| x).max().unwrap_or(0);
+        let max_y = tiles.iter().map(|&(_, y)| y).max().unwrap_or(0);
+        let enclosed = count_enclosed(&tiles, tiles.len());
+        PipeLoop {
+            tiles,
+            bounding_box: ((min_x, min_y), (max_x, max_y)),
+            enclosed,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+    pub fn contains(&self, pos: (usize, usize)) -> bool {
+        self.tiles.contains(&pos)
+    }
+}
+
+/// Tiles inside a loop from its corners in walking order and the number of
+/// tiles on the loop. The shoelace formula gives the area, Pick's theorem
+/// `A = I + B / 2 - 1` the tiles inside.
+pub fn count_enclosed(vertices: &[(usize, usize)], boundary: usize) -> usize {
+    let mut double_area: isize = 0;
+    for (index, &(x0, y0)) in vertices.iter().enumerate() {
+        let (x1, y1) = vertices[(index + 1) % vertices.len()];
+        double_area += x0 as isize * y1 as isize - x1 as isize * y0 as isize;
+    }
+    (double_area.unsigned_abs() + 2 - boundary) / 2
+}
+
+pub struct Path {
+    pub tiles: TileMap,
+    pub current_position: (usize, usize),
+    pub path: Vec<(usize, usize)>,
+    direction: Direction,
+}
+
+impl Path {
+    pub fn with(tiles: TileMap) -> Self {
+        Path {
+            current_position: tiles.starting_position,
+            tiles,
+            path: Vec::new(),
+            direction: Direction::North,
+        }
+    }
+    pub fn setup(&mut self) {
+        let start = self.tiles.starting_position;
+        self.path.push(start);
+        self.current_position = start;
+        self.direction = self.tiles.get(start).expect("Should be there").exits()[0];
+    }
+    /// Follows the loop from the starting position until it is back there and
+    /// returns the loop's tiles in walking order.
+    pub fn find_path(&mut self) -> Result<&[(usize, usize)], StartError> {
+        while let Some(next_pipe) = self.get_next_pipe() {
+            if next_pipe == self.tiles.starting_position {
+                return Ok(&self.path);
+            }
+            self.path.push(next_pipe);
+        }
+        Err(StartError::Unclosed)
+    }
+    /// Steps out of the current pipe through the exit it wasn't entered from,
+    /// so every tile is only looked at once.
+    fn get_next_pipe(&mut self) -> Option<(usize, usize)> {
+        let pos = self
+            .tiles
+            .neighbour(self.current_position, self.direction)?;
+        let entry = self.direction.opposite();
+        let exits = self.tiles.get(pos)?.exits();
+        if !exits.contains(&entry) {
+            return None;
+        }
+        self.current_position = pos;
+        self.direction = *exits.iter().find(|&&exit| exit != entry)?;
+        Some(pos)
+    }
+    /// The corners of the loop in walking order, the straight pipes in between
+    /// don't change the shape of the polygon.
+    pub fn get_vertices(&self) -> Vec<(usize, usize)> {
+        self.path
+            .iter()
+            .copied()
+            .filter(|&pos| !matches!(self.tiles.get(pos), Some(Tile::NorthSouth | Tile::EastWest)))
+            .collect()
+    }
+}
+
+// Rendering
+
+const DIM: &str = "\x1b[2m";
+const BOLD: &str = "\x1b[1m";
+const GREEN: &str = "\x1b[32m";
+const BLUE: &str = "\x1b[34m";
+const RESET: &str = "\x1b[0m";
+
+/// Draws the loop with box characters. With colours the pipes that aren't
+/// part of the loop are dimmed and enclosed tiles are green, outside ones
+/// blue. Without colours those are shown as `I` and `O` instead.
+pub struct Renderer<'a> {
+    tiles: &'a TileMap,
+    on_loop: Vec<Vec<bool>>,
+    enclosed: Option<Vec<Vec<bool>>>,
+    colour: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(tiles: &'a TileMap, path: &[(usize, usize)]) -> Self {
+        let mut on_loop: Vec<Vec<bool>> = tiles
+            .tiles
+            .iter()
+            .map(|row| vec![false; row.len()])
+            .collect();
+        for &(x, y) in path {
+            on_loop[y][x] = true;
+        }
+        Renderer {
+            tiles,
+            on_loop,
+            enclosed: None,
+            colour: io::stdout().is_terminal(),
+        }
+    }
+    pub fn with_enclosed(mut self, enclosed: Vec<Vec<bool>>) -> Self {
+        self.enclosed = Some(enclosed);
+        self
+    }
+    pub fn with_colour(mut self, colour: bool) -> Self {
+        self.colour = colour;
+        self
+    }
+    pub fn render(&self) -> String {
+        let mut result = String::new();
+        for (y, row) in self.tiles.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                self.render_tile(&mut result, (x, y), tile);
+            }
+            result.push('\n');
+        }
+        result
+    }
+    fn render_tile(&self, result: &mut String, (x, y): (usize, usize), tile: &Tile) {
+        let enclosed = self.enclosed.as_ref().map(|enclosed| enclosed[y][x]);
+        if self.on_loop[y][x] {
+            if !self.colour {
+                result.push(tile.to_box_char());
+            } else if (x, y) == self.tiles.starting_position {
+                result.push_str(&format!("{BOLD}{}{RESET}", tile.to_box_char()));
+            } else {
+                result.push(tile.to_box_char());
+            }
+            return;
+        }
+        if !self.colour {
+            result.push(match enclosed {
+                Some(true) => 'I',
+                Some(false) => 'O',
+                None => tile.to_char(),
+            });
+            return;
+        }
+        let colour = match enclosed {
+            Some(true) => GREEN,
+            Some(false) => BLUE,
+            None => "",
+        };
+        let glyph = match tile {
+            Tile::Ground => '·',
+            _ => tile.to_box_char(),
+        };
+        result.push_str(&format!("{colour}{DIM}{glyph}{RESET}"));
+    }
+}
+
+fn get_starting_position(tiles: &[Vec<Tile>]) -> Option<(usize, usize)> {
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            if let Tile::StartingPosition = tile {
+                return Some((x, y));
+            }
+        }
+    }
+    None
+}
+
+pub fn parse(input: &str) -> Result<TileMap, StartError> {
+    let mut result = Vec::new();
+    for line in input.lines() {
+        let mut row = Vec::new();
+        for c in line.chars() {
+            let tile = Tile::from(c);
+            row.push(tile);
+        }
+        result.push(row);
+    }
+    let starting_position = get_starting_position(&result).ok_or(StartError::Missing)?;
+    let mut tile_map = TileMap {
+        tiles: result,
+        starting_position,
+    };
+    tile_map.resolve_starting_position()?;
+    Ok(tile_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_starting_tile() {
+        let cases = [
+            (".....\n.S-7.\n.|.|.\n.L-J.\n.....\n", Tile::SouthEast),
+            ("F-7\n|.|\nL-S\n", Tile::NorthWest),
+            ("F7.\n|L7\nS-J\n", Tile::NorthEast),
+            ("F-S-7\n|...|\nL---J\n", Tile::EastWest),
+        ];
+        for (input, expected) in cases {
+            let tile_map = parse(input).unwrap();
+            assert_eq!(tile_map.get(tile_map.starting_position), Some(&expected));
+        }
+    }
+
+    #[test]
+    fn trace_long_loop() {
+        // Snake through a 140x140 map and come back along the first column
+        let (width, height) = (140, 140);
+        let mut cycle = Vec::new();
+        for y in 0..height {
+            let xs: Vec<usize> = if y == 0 {
+                (0..width).collect()
+            } else if y % 2 == 1 {
+                (1..width).rev().collect()
+            } else {
+                (1..width).collect()
+            };
+            cycle.extend(xs.into_iter().map(|x| (x, y)));
+        }
+        cycle.extend((1..height).rev().map(|y| (0, y)));
+
+        let direction = |(x0, y0): (usize, usize), (x1, y1): (usize, usize)| {
+            Direction::iter()
+                .find(|direction| {
+                    let (dx, dy) = direction.get_indices();
+                    (x0.wrapping_add_signed(dx), y0.wrapping_add_signed(dy)) == (x1, y1)
+                })
+                .unwrap()
+        };
+        let mut map = vec![vec!['.'; width]; height];
+        for (index, &pos) in cycle.iter().enumerate() {
+            let prev = cycle[(index + cycle.len() - 1) % cycle.len()];
+            let next = cycle[(index + 1) % cycle.len()];
+            let tile = Tile::with_exits(direction(pos, prev), direction(pos, next)).unwrap();
+            map[pos.1][pos.0] = tile.to_char();
+        }
+        map[0][0] = 'S';
+        let input: String = map
+            .iter()
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect();
+
+        let mut path = Path::with(parse(&input).unwrap());
+        path.setup();
+        assert_eq!(path.find_path().unwrap().len(), width * height);
+        assert_eq!(path.get_vertices().len(), 2 * height);
+    }
+
+    #[test]
+    fn render_plain() {
+        let input = "7-F7-\n.FJ|7\nSJLL7\n|F--J\nLJ.LJ\n";
+        let mut path = Path::with(parse(input).unwrap());
+        path.setup();
+        path.find_path().unwrap();
+        let rendered = Renderer::new(&path.tiles, &path.path)
+            .with_colour(false)
+            .render();
+        assert_eq!(rendered, "7-┌┐-\n.┌┘│7\n┌┘L└┐\n│┌──┘\n└┘.LJ\n");
+
+        let mut enclosed = vec![vec![false; 5]; 5];
+        enclosed[2][2] = true;
+        let rendered = Renderer::new(&path.tiles, &path.path)
+            .with_enclosed(enclosed)
+            .with_colour(false)
+            .render();
+        assert_eq!(rendered, "OO┌┐O\nO┌┘│O\n┌┘I└┐\n│┌──┘\n└┘OOO\n");
+    }
+
+    #[test]
+    fn find_all_loops() {
+        let input = "\
+S-7.F-----7
+|.|.|F---7|
+L-J.||...||
+.F7.|L---J|
+.LJ.L-----J
+-7.F-J.|..F
+";
+        let report = parse(input).unwrap().find_loops();
+        assert_eq!(report.loops.len(), 4);
+
+        let start = &report.loops[0];
+        assert!(start.contains((0, 0)));
+        assert_eq!(
+            (start.len(), start.bounding_box, start.enclosed),
+            (8, ((0, 0), (2, 2)), 1)
+        );
+        let outer = &report.loops[1];
+        assert_eq!(
+            (outer.len(), outer.bounding_box, outer.enclosed),
+            (20, ((4, 0), (10, 4)), 15)
+        );
+        let inner = &report.loops[2];
+        assert_eq!((inner.len(), inner.enclosed), (12, 3));
+        let small = &report.loops[3];
+        assert_eq!(
+            (small.len(), small.bounding_box, small.enclosed),
+            (4, ((1, 3), (2, 4)), 0)
+        );
+
+        let mut fragments: Vec<usize> = report.fragments.iter().map(Vec::len).collect();
+        fragments.sort();
+        assert_eq!(fragments, vec![1, 1, 2, 3]);
+    }
+
+    #[test]
+    fn invalid_starting_tile() {
+        assert_eq!(parse("...\n.-.\n...\n").err(), Some(StartError::Missing));
+        assert_eq!(
+            parse("...\n.S.\n...\n").err(),
+            Some(StartError::Connections(0))
+        );
+        assert_eq!(
+            parse("...\n.S-\n...\n").err(),
+            Some(StartError::Connections(1))
+        );
+        assert_eq!(
+            parse(".|.\n-S-\n.|.\n").err(),
+            Some(StartError::Connections(4))
+        );
+
+        let mut path = Path::with(parse(".....\n-S--.\n.....\n").unwrap());
+        path.setup();
+        assert_eq!(path.find_path().err(), Some(StartError::Unclosed));
+    }
+}
@@ -1,16 +1,33 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::fmt;
+use std::fs;
+use std::str::FromStr;
 
 use nom::{
-    branch::alt, bytes::complete::tag, character::complete::char, combinator::map, multi::many1,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, line_ending, space0, space1, u128, u64},
+    combinator::{eof, map},
+    multi::{many0, many1},
+    sequence::{terminated, tuple},
     IResult,
 };
 
+const USAGE: &str = "Usage: part2 [--enlargement <n>] [--row-enlargement <n>] \
+[--col-enlargement <n>] [--factors <file>] [--coords] [--nearest <galaxy> <k>] [--farthest] \
+[--matrix <file.csv|->]";
+
 fn main() {
     println!("Part 2");
     let input = include_str!("../../input.txt");
-    let result = solve(input);
-    println!("Result = {}", result);
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        let result = solve(input);
+        println!("Result = {}", result);
+    } else {
+        run_queries(input, &args);
+    }
 }
 
 fn solve(input: &str) -> String {
@@ -21,13 +38,96 @@ fn solve(input: &str) -> String {
     space.get_sum_smallest_distance().to_string()
 }
 
+enum Query {
+    Coords,
+    Nearest(usize, usize),
+    Farthest,
+    Matrix(String),
+}
+
+/// Applies every setting first, so they hold no matter where they are given,
+/// then answers the queries in order.
+fn run_queries(input: &str, args: &[String]) {
+    let mut space = Space::from(input);
+    space.cosmic_expansion();
+
+    let mut queries = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--enlargement" => space.set_enlargement(next_number(&mut args)),
+            "--row-enlargement" => space.set_row_enlargement(next_number(&mut args)),
+            "--col-enlargement" => space.set_col_enlargement(next_number(&mut args)),
+            "--factors" => {
+                let factors =
+                    fs::read_to_string(next_path(&mut args)).expect("Could not read the factors");
+                space.set_line_factors(&factors);
+            }
+            "--coords" => queries.push(Query::Coords),
+            "--nearest" => {
+                let galaxy = next_number(&mut args);
+                queries.push(Query::Nearest(galaxy, next_number(&mut args)));
+            }
+            "--farthest" => queries.push(Query::Farthest),
+            "--matrix" => queries.push(Query::Matrix(next_path(&mut args).clone())),
+            _ => panic!("{USAGE}"),
+        }
+    }
+
+    for query in queries {
+        match query {
+            Query::Coords => {
+                for (galaxy, (x, y)) in space.get_galaxies().iter().enumerate() {
+                    println!("{galaxy}: ({x}, {y})");
+                }
+            }
+            Query::Nearest(galaxy, k) => match space.get_nearest_galaxies(galaxy, k) {
+                Some(nearest) => {
+                    for (other, distance) in nearest {
+                        println!("{other}: {distance}");
+                    }
+                }
+                None => panic!("Galaxy {galaxy} does not exist"),
+            },
+            Query::Farthest => match space.get_farthest_pair() {
+                Some((a, b, distance)) => println!("{a} <-> {b}: {distance}"),
+                None => println!("Less than two galaxies"),
+            },
+            Query::Matrix(path) => {
+                let csv = space.get_distance_matrix_csv();
+                match path.as_str() {
+                    "-" => print!("{csv}"),
+                    path => fs::write(path, csv).expect("Could not write the matrix"),
+                }
+            }
+        }
+    }
+    println!("Sum = {}", space.get_sum_smallest_distance());
+}
+
+fn next_number<'a, T: FromStr>(args: &mut impl Iterator<Item = &'a String>) -> T {
+    args.next().and_then(|arg| arg.parse().ok()).expect(USAGE)
+}
+
+/// A file argument, which can't be the next flag.
+fn next_path<'a>(args: &mut impl Iterator<Item = &'a String>) -> &'a String {
+    args.next()
+        .filter(|path| !path.starts_with("--"))
+        .expect(USAGE)
+}
+
 // Logic
 
 struct Space {
     objects: Vec<Vec<SpaceObject>>,
-    expansion_rows: Vec<usize>,
-    expansion_cols: Vec<usize>,
-    enlargement: usize,
+    /// Indices of the lines without a galaxy.
+    empty_rows: Vec<usize>,
+    /// Indices of the columns without a galaxy.
+    empty_cols: Vec<usize>,
+    row_enlargement: u128,
+    col_enlargement: u128,
+    row_factors: HashMap<usize, u128>,
+    col_factors: HashMap<usize, u128>,
 }
 
 impl From<&str> for Space {
@@ -35,20 +135,24 @@ impl From<&str> for Space {
         let (_, objects) = parse_galaxy(input).unwrap();
         Self {
             objects,
-            expansion_rows: Vec::new(),
-            expansion_cols: Vec::new(),
-            enlargement: 1_000_000,
+            empty_rows: Vec::new(),
+            empty_cols: Vec::new(),
+            row_enlargement: 1_000_000,
+            col_enlargement: 1_000_000,
+            row_factors: HashMap::new(),
+            col_factors: HashMap::new(),
         }
     }
 }
 
 impl Space {
+    /// Galaxies as `(x, y)`, the column and the line they are in.
     fn gen_galaxy_index(&self) -> Vec<(usize, usize)> {
         let mut index = Vec::new();
-        for (col, objects) in self.objects.iter().enumerate() {
-            for (row, object) in objects.iter().enumerate() {
+        for (y, objects) in self.objects.iter().enumerate() {
+            for (x, object) in objects.iter().enumerate() {
                 if let SpaceObject::Galaxy = object {
-                    index.push((row, col));
+                    index.push((x, y));
                 }
             }
         }
@@ -57,13 +161,13 @@ impl Space {
 
     fn cosmic_expansion(&mut self) {
         // From 0 to size of objects
-        let mut rows: HashSet<usize> = HashSet::from_iter(0..self.objects.first().unwrap().len());
-        let mut cols: HashSet<usize> = HashSet::from_iter(0..self.objects.len());
-        for (col, objects) in self.objects.iter().enumerate() {
-            for (row, object) in objects.iter().enumerate() {
+        let mut rows: HashSet<usize> = HashSet::from_iter(0..self.objects.len());
+        let mut cols: HashSet<usize> = HashSet::from_iter(0..self.objects.first().unwrap().len());
+        for (y, objects) in self.objects.iter().enumerate() {
+            for (x, object) in objects.iter().enumerate() {
                 if let SpaceObject::Galaxy = object {
-                    rows.remove(&row);
-                    cols.remove(&col);
+                    rows.remove(&y);
+                    cols.remove(&x);
                 }
             }
         }
@@ -71,60 +175,145 @@ impl Space {
         let mut cols: Vec<usize> = Vec::from_iter(cols);
         rows.sort();
         cols.sort();
-        // TODO genau umgekehrt
-        self.expansion_rows.append(&mut rows);
-        self.expansion_cols.append(&mut cols);
+        self.empty_rows.append(&mut rows);
+        self.empty_cols.append(&mut cols);
+    }
+
+    /// Galaxy positions after applying the expansion, computed once up front.
+    fn gen_expanded_galaxy_index(&self) -> Vec<(u128, u128)> {
+        let x_positions = Self::gen_expanded_positions(
+            self.objects.first().map_or(0, |objects| objects.len()),
+            &self.empty_cols,
+            self.col_enlargement,
+            &self.col_factors,
+        );
+        let y_positions = Self::gen_expanded_positions(
+            self.objects.len(),
+            &self.empty_rows,
+            self.row_enlargement,
+            &self.row_factors,
+        );
+        self.gen_galaxy_index()
+            .into_iter()
+            .map(|(x, y)| (x_positions[x], y_positions[y]))
+            .collect()
+    }
+
+    /// Position of every index on an axis once each line is replaced by as
+    /// many lines as its factor. Empty lines default to `enlargement`, all
+    /// others to 1, unless `factors` says otherwise.
+    fn gen_expanded_positions(
+        len: usize,
+        expansions: &[usize],
+        enlargement: u128,
+        factors: &HashMap<usize, u128>,
+    ) -> Vec<u128> {
+        let mut positions = Vec::with_capacity(len);
+        let mut expansions = expansions.iter().peekable();
+        let mut position = 0;
+        for index in 0..len {
+            positions.push(position);
+            let empty = expansions.next_if_eq(&&index).is_some();
+            let factor = match factors.get(&index) {
+                Some(&factor) => factor,
+                None if empty => enlargement,
+                None => 1,
+            };
+            position += factor;
+        }
+        positions
     }
 
-    fn get_sum_smallest_distance(&self) -> usize {
+    fn get_sum_smallest_distance(&self) -> u128 {
+        let (xs, ys): (Vec<u128>, Vec<u128>) = self.gen_expanded_galaxy_index().into_iter().unzip();
+        Self::get_sum_axis_distance(xs) + Self::get_sum_axis_distance(ys)
+    }
+
+    /// Sum of `|a - b|` over all pairs: once sorted, every value is the larger
+    /// one of the pair with all values before it.
+    fn get_sum_axis_distance(mut values: Vec<u128>) -> u128 {
+        values.sort_unstable();
         let mut sum = 0;
-        let mut galaxy_index = self.gen_galaxy_index();
-        loop {
-            if galaxy_index.is_empty() {
-                break;
-            }
-            let start = galaxy_index.pop().unwrap();
-            for end in galaxy_index.iter() {
-                sum += self.get_sum_smallest_distance_alt(start, *end);
-            }
+        let mut prefix = 0;
+        for (count, value) in values.into_iter().enumerate() {
+            sum += value * count as u128 - prefix;
+            prefix += value;
         }
         sum
     }
 
-    fn get_smallest_distance(&self, (x0, y0): (usize, usize), (x1, y1): (usize, usize)) -> usize {
-        let x_range = if x0 > x1 { x1..x0 } else { x0..x1 };
-        let x_offset = self
-            .expansion_rows
-            .iter()
-            .filter(|&row| x_range.contains(row))
-            .count();
-        let y_range = if y0 > y1 { y1..y0 } else { y0..y1 };
-        let y_offset = self
-            .expansion_cols
-            .iter()
-            .filter(|&col| y_range.contains(col))
-            .count();
+    // Queries
+    /// Expanded coordinates of every galaxy, galaxies are identified by their
+    /// index in this list.
+    pub fn get_galaxies(&self) -> Vec<(u128, u128)> {
+        self.gen_expanded_galaxy_index()
+    }
+
+    pub fn get_distance((x0, y0): (u128, u128), (x1, y1): (u128, u128)) -> u128 {
+        x0.abs_diff(x1) + y0.abs_diff(y1)
+    }
 
-        (x0 as isize).abs_diff(x1 as isize)
-            + (y0 as isize).abs_diff(y1 as isize)
-            + ((x_offset + y_offset) * self.enlargement)
+    /// The `k` galaxies closest to `galaxy` as `(galaxy, distance)`, closest first.
+    /// `None` if there is no such galaxy.
+    pub fn get_nearest_galaxies(&self, galaxy: usize, k: usize) -> Option<Vec<(usize, u128)>> {
+        let galaxies = self.get_galaxies();
+        let start = *galaxies.get(galaxy)?;
+        let mut distances: Vec<(usize, u128)> = galaxies
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != galaxy)
+            .map(|(other, &end)| (other, Self::get_distance(start, end)))
+            .collect();
+        distances.sort_by_key(|&(other, distance)| (distance, other));
+        distances.truncate(k);
+        Some(distances)
     }
 
-    fn get_sum_smallest_distance_alt(
-        &self,
-        (x0, y0): (usize, usize),
-        (x1, y1): (usize, usize),
-    ) -> usize {
-        let nx0 = x0
-            + self.expansion_rows.iter().filter(|&row| *row < x0).count() * (self.enlargement - 1);
-        let nx1 = x1
-            + self.expansion_rows.iter().filter(|&row| *row < x1).count() * (self.enlargement - 1);
-        let ny0 = y0
-            + self.expansion_cols.iter().filter(|&col| *col < y0).count() * (self.enlargement - 1);
-        let ny1 = y1
-            + self.expansion_cols.iter().filter(|&col| *col < y1).count() * (self.enlargement - 1);
+    /// The two galaxies furthest apart as `(galaxy, galaxy, distance)`.
+    ///
+    /// The Manhattan distance is the largest spread of either `x + y` or
+    /// `x - y`, so one pass over the galaxies is enough.
+    pub fn get_farthest_pair(&self) -> Option<(usize, usize, u128)> {
+        let galaxies = self.get_galaxies();
+        if galaxies.len() < 2 {
+            return None;
+        }
+        let mut best = None;
+        let rotations: [fn(u128, u128) -> i128; 2] =
+            [|x, y| x as i128 + y as i128, |x, y| x as i128 - y as i128];
+        for rotate in rotations {
+            let values = galaxies.iter().map(|&(x, y)| rotate(x, y));
+            let (min, _) = values
+                .clone()
+                .enumerate()
+                .min_by_key(|&(_, value)| value)
+                .unwrap();
+            let (max, _) = values.enumerate().max_by_key(|&(_, value)| value).unwrap();
+            let distance = Self::get_distance(galaxies[min], galaxies[max]);
+            if best.is_none_or(|(_, _, best)| distance > best) {
+                best = Some((min.min(max), min.max(max), distance));
+            }
+        }
+        best
+    }
 
-        (nx0 as isize).abs_diff(nx1 as isize) + (ny0 as isize).abs_diff(ny1 as isize)
+    /// Distances between all galaxies, with the galaxy indices as header row
+    /// and first column.
+    pub fn get_distance_matrix_csv(&self) -> String {
+        let galaxies = self.get_galaxies();
+        let mut csv = String::new();
+        for galaxy in 0..galaxies.len() {
+            csv.push_str(&format!(",{galaxy}"));
+        }
+        csv.push('\n');
+        for (galaxy, &start) in galaxies.iter().enumerate() {
+            csv.push_str(&galaxy.to_string());
+            for &end in galaxies.iter() {
+                csv.push_str(&format!(",{}", Self::get_distance(start, end)));
+            }
+            csv.push('\n');
+        }
+        csv
     }
 
     // Debug
@@ -152,9 +341,26 @@ impl Space {
     pub fn objects(&self) -> &Vec<Vec<SpaceObject>> {
         &self.objects
     }
-    #[allow(dead_code)]
-    pub fn set_enlargement(&mut self, enlargement: usize) {
-        self.enlargement = enlargement;
+    pub fn set_enlargement(&mut self, enlargement: u128) {
+        self.row_enlargement = enlargement;
+        self.col_enlargement = enlargement;
+    }
+    pub fn set_row_enlargement(&mut self, enlargement: u128) {
+        self.row_enlargement = enlargement;
+    }
+    pub fn set_col_enlargement(&mut self, enlargement: u128) {
+        self.col_enlargement = enlargement;
+    }
+    /// Per line factors, one `row <index> <factor>` or `col <index> <factor>`
+    /// per line, overriding the enlargement of that single line.
+    pub fn set_line_factors(&mut self, input: &str) {
+        let (_, factors) = parse_line_factors(input).expect("Factors not in the right format");
+        for (axis, index, factor) in factors {
+            match axis {
+                Axis::Row => self.row_factors.insert(index, factor),
+                Axis::Col => self.col_factors.insert(index, factor),
+            };
+        }
     }
 }
 
@@ -192,6 +398,39 @@ pub fn parse_galaxy(input: &str) -> IResult<&str, Vec<Vec<SpaceObject>>> {
 
     Ok((input, objects))
 }
+
+pub enum Axis {
+    Row,
+    Col,
+}
+
+fn parse_line_factor(input: &str) -> IResult<&str, (Axis, usize, u128)> {
+    let (input, (_, axis, _, index, _, factor, _)) = tuple((
+        space0,
+        alt((
+            map(tag("row"), |_| Axis::Row),
+            map(tag("col"), |_| Axis::Col),
+        )),
+        space1,
+        u64,
+        space1,
+        u128,
+        space0,
+    ))(input)?;
+
+    Ok((input, (axis, index as usize, factor)))
+}
+
+pub fn parse_line_factors(input: &str) -> IResult<&str, Vec<(Axis, usize, u128)>> {
+    let (input, _) = many0(line_ending)(input)?;
+    let (input, factors) = many0(terminated(
+        parse_line_factor,
+        alt((map(many1(line_ending), |_| ()), map(eof, |_| ()))),
+    ))(input)?;
+    let (input, _) = eof(input)?;
+
+    Ok((input, factors))
+}
 #[cfg(test)]
 mod tests {
     use crate::Space;
@@ -237,5 +476,90 @@ mod tests {
 
         assert_eq!(output, "1030".to_string());
     }
-}
 
+    #[test]
+    fn matches_pairwise_sum() {
+        let input = include_str!("../../input.txt");
+        let mut space = Space::from(input);
+        space.cosmic_expansion();
+        for (row_enlargement, col_enlargement) in [(1, 1), (2, 2), (10, 100), (1_000_000, 3)] {
+            space.set_row_enlargement(row_enlargement);
+            space.set_col_enlargement(col_enlargement);
+            let expand = |value: usize, expansions: &[usize], enlargement: u128| {
+                value as u128
+                    + expansions.iter().filter(|&&e| e < value).count() as u128 * (enlargement - 1)
+            };
+            let galaxies: Vec<(u128, u128)> = space
+                .gen_galaxy_index()
+                .into_iter()
+                .map(|(x, y)| {
+                    (
+                        expand(x, &space.empty_cols, col_enlargement),
+                        expand(y, &space.empty_rows, row_enlargement),
+                    )
+                })
+                .collect();
+            let mut expected = 0;
+            for (i, &(x0, y0)) in galaxies.iter().enumerate() {
+                for &(x1, y1) in galaxies.iter().skip(i + 1) {
+                    expected += x0.abs_diff(x1) + y0.abs_diff(y1);
+                }
+            }
+            assert_eq!(space.get_sum_smallest_distance(), expected);
+        }
+    }
+
+    #[test]
+    fn line_factors() {
+        let input = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....
+";
+        let mut space = Space::from(input);
+        space.cosmic_expansion();
+        space.set_enlargement(1_000_000_000_000);
+        assert_eq!(space.get_sum_smallest_distance(), 82_000_000_000_210);
+
+        // Same as an enlargement of 10 for every empty line
+        space.set_enlargement(1);
+        space.set_line_factors("row 3 10\nrow 7 10\n\ncol 2 10\ncol 5 10\ncol 8 10\n");
+        assert_eq!(space.get_sum_smallest_distance(), 1030);
+    }
+
+    #[test]
+    fn galaxy_queries() {
+        let input = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....
+";
+        let mut space = Space::from(input);
+        space.cosmic_expansion();
+        space.set_enlargement(2);
+
+        // Galaxies 5 and 9 from the puzzle text
+        assert_eq!(space.get_galaxies()[4], (1, 6));
+        assert_eq!(space.get_galaxies()[8], (5, 11));
+        assert_eq!(space.get_nearest_galaxies(4, 2), Some(vec![(2, 5), (7, 6)]));
+        assert_eq!(space.get_nearest_galaxies(9, 2), None);
+        assert_eq!(space.get_farthest_pair(), Some((1, 7, 19)));
+
+        let csv = space.get_distance_matrix_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(",0,1,2,3,4,5,6,7,8"));
+        assert_eq!(lines.nth(4).unwrap().split(',').nth(9), Some("9"));
+    }
+}